@@ -0,0 +1,26 @@
+use std::env;
+use std::process::Command;
+
+//Returns minor version of rustc, if it can be determined.
+fn rustc_minor() -> Option<u32> {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let output = Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    //rustc 1.81.0 (eeb90cda1 2024-09-04)
+    let mut parts = version.split_whitespace().nth(1)?.split('.');
+    match parts.next() {
+        Some("1") => parts.next()?.parse().ok(),
+        _ => None,
+    }
+}
+
+fn main() {
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_owned());
+    println!("cargo:rustc-env=LAZY_PANIC_TARGET={}", target);
+
+    println!("cargo:rustc-check-cfg=cfg(panic_hook_info)");
+    let minor = rustc_minor().unwrap_or(0);
+    if minor >= 81 {
+        println!("cargo:rustc-cfg=panic_hook_info");
+    }
+}
@@ -0,0 +1,208 @@
+//! Procedural macros for `lazy-panic`
+//!
+//!Use them via `derive` and `macros` features of `lazy-panic`.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Data, DeriveInput, ItemFn, LitInt, Token, Type};
+
+const ATTR: &str = "panic_format";
+
+#[derive(Default)]
+struct Components {
+    prefix: Option<Type>,
+    info: Option<Type>,
+    suffix: Option<Type>,
+    backtrace: Option<Type>,
+    writer: Option<Type>,
+    two_stage: bool,
+}
+
+impl Components {
+    fn slot(&mut self, name: &syn::Path) -> Option<&mut Option<Type>> {
+        if name.is_ident("prefix") {
+            Some(&mut self.prefix)
+        } else if name.is_ident("info") {
+            Some(&mut self.info)
+        } else if name.is_ident("suffix") {
+            Some(&mut self.suffix)
+        } else if name.is_ident("backtrace") {
+            Some(&mut self.backtrace)
+        } else if name.is_ident("writer") {
+            Some(&mut self.writer)
+        } else {
+            None
+        }
+    }
+
+    //`#[panic_format(prefix = Timestamp, two_stage)]`
+    fn parse_struct_attr(&mut self, attr: &syn::Attribute) -> syn::Result<()> {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("two_stage") {
+                self.two_stage = true;
+                return Ok(());
+            }
+
+            let value = meta.value()?.parse::<Type>()?;
+            match self.slot(&meta.path) {
+                Some(slot) => {
+                    *slot = Some(value);
+                    Ok(())
+                },
+                None => Err(meta.error("expected one of: prefix, info, suffix, backtrace, writer, two_stage")),
+            }
+        })
+    }
+
+    //`#[panic_format(prefix)] prefix: Timestamp`
+    fn parse_field_attr(&mut self, attr: &syn::Attribute, ty: &Type) -> syn::Result<()> {
+        attr.parse_nested_meta(|meta| match self.slot(&meta.path) {
+            Some(slot) => {
+                *slot = Some(ty.clone());
+                Ok(())
+            },
+            None => Err(meta.error("expected one of: prefix, info, suffix, backtrace, writer")),
+        })
+    }
+}
+
+///Implements `PanicFormat` with components selected by `panic_format` attribute.
+///
+///Components are specified on struct as `#[panic_format(prefix = Type, ...)]`
+///or on field as `#[panic_format(prefix)]`, taking field's type.
+///
+///- `prefix`, `info`, `suffix` and `backtrace` default to `Simple`
+///- `writer` must implement `io::Write` and `Default`, defaults to writer of `Simple`
+///- `two_stage` enables `PanicFormat::TWO_STAGE`
+#[proc_macro_derive(PanicFormat, attributes(panic_format))]
+pub fn derive_panic_format(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive(&input) {
+        Ok(output) => output.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let mut components = Components::default();
+
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident(ATTR)) {
+        components.parse_struct_attr(attr)?;
+    }
+
+    match input.data {
+        Data::Struct(ref data) => data.fields.iter().try_for_each(|field| {
+            field.attrs.iter()
+                       .filter(|attr| attr.path().is_ident(ATTR))
+                       .try_for_each(|attr| components.parse_field_attr(attr, &field.ty))
+        })?,
+        _ => return Err(syn::Error::new_spanned(&input.ident, "PanicFormat can be derived only for struct")),
+    }
+
+    let simple = quote!(::lazy_panic::formatter::Simple);
+    let component = |ty: Option<Type>| match ty {
+        Some(ty) => quote!(#ty),
+        None => simple.clone(),
+    };
+    let prefix = component(components.prefix);
+    let info = component(components.info);
+    let suffix = component(components.suffix);
+    let backtrace = component(components.backtrace);
+    let (writer, new_writer) = match components.writer {
+        Some(writer) => (quote!(#writer), quote!(::std::default::Default::default())),
+        None => (quote!(<#simple as ::lazy_panic::formatter::PanicFormat>::Writer), quote!(<#simple as ::lazy_panic::formatter::PanicFormat>::writer())),
+    };
+    let two_stage = components.two_stage;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::lazy_panic::formatter::PanicFormat for #name #ty_generics #where_clause {
+            type Writer = #writer;
+            type Backtrace = #backtrace;
+            type Prefix = #prefix;
+            type PanicInfo = #info;
+            type Suffix = #suffix;
+
+            const TWO_STAGE: bool = #two_stage;
+
+            #[inline]
+            fn writer() -> Self::Writer {
+                #new_writer
+            }
+        }
+    })
+}
+
+struct MainArgs {
+    format: Type,
+    exit_code: Option<LitInt>,
+}
+
+//`Format` or `Format, exit_code = 70`
+impl Parse for MainArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let format = input.parse()?;
+        let mut exit_code = None;
+
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name = input.parse::<syn::Ident>()?;
+            if name != "exit_code" {
+                return Err(syn::Error::new_spanned(name, "expected exit_code"));
+            }
+            input.parse::<Token![=]>()?;
+            exit_code = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+
+        Ok(MainArgs {
+            format,
+            exit_code,
+        })
+    }
+}
+
+///Installs hook with specified format at start of `main`
+///
+///With `exit_code`, panic of `main` exits process with it.
+///
+///```rust,ignore
+///#[lazy_panic::main(lazy_panic::formatter::Debug, exit_code = 70)]
+///fn main() {
+///}
+///```
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as MainArgs);
+    let mut main = parse_macro_input!(input as ItemFn);
+
+    let format = &args.format;
+    let body = &main.block;
+    let body = match args.exit_code {
+        Some(exit_code) => quote!({
+            ::lazy_panic::hook::install::<#format>();
+            match ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(move || #body)) {
+                Ok(result) => result,
+                Err(_) => ::std::process::exit(#exit_code),
+            }
+        }),
+        None => quote!({
+            ::lazy_panic::hook::install::<#format>();
+            #body
+        }),
+    };
+    main.block = match syn::parse2(body) {
+        Ok(body) => Box::new(body),
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    quote!(#main).into()
+}
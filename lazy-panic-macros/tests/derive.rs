@@ -0,0 +1,54 @@
+#[macro_use]
+extern crate lazy_panic;
+
+use std::marker::PhantomData;
+
+use lazy_panic::formatter::{Empty, JustError, PanicFormat, Pid};
+use lazy_panic::test::format_panic;
+
+#[derive(PanicFormat)]
+#[panic_format(info = JustError, suffix = Empty, writer = Vec<u8>)]
+struct Attributed;
+
+#[derive(PanicFormat)]
+#[panic_format(two_stage)]
+struct Fielded {
+    #[panic_format(prefix)]
+    _prefix: Pid,
+    #[panic_format(info)]
+    _info: JustError,
+    #[panic_format(suffix, backtrace)]
+    _empty: Empty,
+}
+
+#[derive(PanicFormat)]
+struct Defaulted<T> {
+    _marker: PhantomData<T>,
+}
+
+#[test]
+fn should_derive_from_attributes() {
+    assert!(format_panic::<Attributed, _>("lolka").starts_with("Panic: lolka"));
+    assert_eq!(Attributed::writer(), Vec::<u8>::new());
+}
+
+#[test]
+fn should_derive_from_fields() {
+    let report = format_panic::<Fielded, _>("lolka");
+    assert!(report.starts_with("[pid "), "{}", report);
+    assert!(report.ends_with("] lolka"), "{}", report);
+}
+
+#[test]
+fn should_derive_two_stage() {
+    assert_eq!([Attributed::TWO_STAGE, Fielded::TWO_STAGE, Defaulted::<u8>::TWO_STAGE], [false, true, false]);
+}
+
+#[test]
+fn should_derive_defaults() {
+    let report = format_panic::<Defaulted<u8>, _>("lolka");
+    assert!(report.starts_with("Panic: "), "{}", report);
+    assert!(report.contains(" - lolka"), "{}", report);
+
+    set_panic_message!(Defaulted<u8>);
+}
@@ -0,0 +1,46 @@
+extern crate lazy_panic;
+
+use std::env;
+use std::process;
+
+const CHILD: &str = "LAZY_PANIC_TEST_MAIN_CHILD";
+
+#[lazy_panic::main(lazy_panic::formatter::JustError, exit_code = 70)]
+fn exiting_main() -> u8 {
+    if env::var_os(CHILD).is_some() {
+        panic!("lolka");
+    }
+    1
+}
+
+#[lazy_panic::main(lazy_panic::formatter::JustError)]
+fn plain_main() -> Result<u8, String> {
+    let value = "2".parse::<u8>().map_err(|error| error.to_string())?;
+    Ok(value)
+}
+
+#[test]
+fn should_install_format() {
+    assert_eq!(plain_main(), Ok(2));
+    assert!(lazy_panic::installed_format_name().unwrap().ends_with("JustError"));
+}
+
+#[test]
+#[ignore]
+fn main_child() {
+    exiting_main();
+}
+
+#[test]
+fn should_exit_with_code_on_panic() {
+    assert_eq!(exiting_main(), 1);
+
+    let output = process::Command::new(env::current_exe().expect("To get test executable"))
+                                  .args(["main_child", "--exact", "--ignored", "--test-threads=1"])
+                                  .env(CHILD, "1")
+                                  .output()
+                                  .expect("To run child");
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("lolka"));
+}
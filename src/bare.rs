@@ -0,0 +1,203 @@
+//! Formatting over `core::fmt::Write`, available without `std`.
+//!
+//!Reuses composition of Prefix, `PanicInfo` and Suffix within `#[panic_handler]`
+//!of embedded and kernel-adjacent projects, which build crate with `default-features = false`.
+//!
+//!```rust,ignore
+//!#[panic_handler]
+//!fn panic(info: &core::panic::PanicInfo) -> ! {
+//!    let _ = lazy_panic::bare::Simple::write(&mut uart, info);
+//!    loop {}
+//!}
+//!```
+//!
+//!With `std`, panic hook's info is supported as well.
+
+use core::fmt;
+use core::panic::Location;
+
+///Describes panic's information available to components.
+///
+///Implemented for `core::panic::PanicInfo` and, with `std`, for info of panic hook.
+pub trait Info {
+    ///Returns location of panic, if any.
+    fn location(&self) -> Option<&Location<'_>>;
+
+    ///Writes panic's message.
+    fn write_message<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result;
+}
+
+impl Info for ::core::panic::PanicInfo<'_> {
+    #[inline]
+    fn location(&self) -> Option<&Location<'_>> {
+        ::core::panic::PanicInfo::location(self)
+    }
+
+    //`PanicInfo::message` is stable since the same Rust 1.81
+    #[cfg(panic_hook_info)]
+    #[allow(clippy::incompatible_msrv)]
+    #[inline]
+    fn write_message<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        write!(writer, "{}", self.message())
+    }
+
+    #[cfg(not(panic_hook_info))]
+    #[inline]
+    fn write_message<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        match self.payload().downcast_ref::<&str>() {
+            Some(message) => writer.write_str(message),
+            None => writer.write_str("Box<dyn Any>"),
+        }
+    }
+}
+
+//Before `PanicHookInfo` hook received the same `core::panic::PanicInfo`
+#[cfg(all(feature = "std", panic_hook_info))]
+impl Info for ::PanicHookInfo<'_> {
+    #[inline]
+    fn location(&self) -> Option<&Location<'_>> {
+        ::PanicHookInfo::location(self)
+    }
+
+    #[inline]
+    fn write_message<W: fmt::Write>(&self, writer: &mut W) -> fmt::Result {
+        writer.write_str(&::payload::to_string(self.payload()))
+    }
+}
+
+///Describes how to write panic's message prefix.
+pub trait Prefix {
+    fn write_in<W: fmt::Write>(writer: &mut W) -> fmt::Result;
+}
+
+///Describes how to write panic's information.
+pub trait PanicInfo {
+    fn write_in<W: fmt::Write, I: Info + ?Sized>(writer: &mut W, info: &I) -> fmt::Result;
+}
+
+///Describes how to write panic's message suffix.
+pub trait Suffix {
+    fn write_in<W: fmt::Write>(writer: &mut W) -> fmt::Result;
+}
+
+///Panic formatter writing Prefix, `PanicInfo` and Suffix into `core::fmt::Write`
+pub trait PanicFormat {
+    type Prefix: Prefix;
+    type PanicInfo: PanicInfo;
+    type Suffix: Suffix;
+
+    ///Writes each component into `writer`, stopping at first error.
+    fn write<W: fmt::Write, I: Info + ?Sized>(writer: &mut W, info: &I) -> fmt::Result {
+        Self::Prefix::write_in(writer)?;
+        Self::PanicInfo::write_in(writer, info)?;
+        Self::Suffix::write_in(writer)
+    }
+}
+
+///Noop configuration.
+///
+///As [PanicFormat](trait.PanicFormat.html) it writes nothing
+pub struct Empty;
+
+impl Prefix for Empty {
+    #[inline]
+    fn write_in<W: fmt::Write>(_: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl PanicInfo for Empty {
+    #[inline]
+    fn write_in<W: fmt::Write, I: Info + ?Sized>(_: &mut W, _: &I) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl Suffix for Empty {
+    #[inline]
+    fn write_in<W: fmt::Write>(_: &mut W) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl PanicFormat for Empty {
+    type Prefix = Self;
+    type PanicInfo = Self;
+    type Suffix = Self;
+}
+
+///Simple configuration, same as [formatter::Simple](../formatter/struct.Simple.html) without metadata and context.
+///
+///For prefix it is constant string `Panic: `
+///
+///For `PanicInfo` it writes `{file}:{line}:{column} - {message}`
+///
+///For suffix it is `\n`
+pub struct Simple;
+
+impl Prefix for Simple {
+    #[inline]
+    fn write_in<W: fmt::Write>(writer: &mut W) -> fmt::Result {
+        writer.write_str("Panic: ")
+    }
+}
+
+impl PanicInfo for Simple {
+    #[inline]
+    fn write_in<W: fmt::Write, I: Info + ?Sized>(writer: &mut W, info: &I) -> fmt::Result {
+        match info.location() {
+            Some(location) => write!(writer, "{}:{}:{} - ", location.file(), location.line(), location.column()),
+            None => writer.write_str("unknown:0:0 - "),
+        }?;
+        info.write_message(writer)
+    }
+}
+
+impl Suffix for Simple {
+    #[inline]
+    fn write_in<W: fmt::Write>(writer: &mut W) -> fmt::Result {
+        writer.write_str("\n")
+    }
+}
+
+impl PanicFormat for Simple {
+    type Prefix = Self;
+    type PanicInfo = Self;
+    type Suffix = Self;
+}
+
+///Writes only message, followed by `\n`
+pub struct JustError;
+
+impl PanicInfo for JustError {
+    #[inline]
+    fn write_in<W: fmt::Write, I: Info + ?Sized>(writer: &mut W, info: &I) -> fmt::Result {
+        info.write_message(writer)
+    }
+}
+
+impl PanicFormat for JustError {
+    type Prefix = Empty;
+    type PanicInfo = Self;
+    type Suffix = Simple;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use test::with_panic_info;
+    use super::{JustError, PanicFormat, Simple};
+
+    #[test]
+    fn should_write_bare_format() {
+        let report = with_panic_info("lolka 1".to_owned(), |info| {
+            let mut report = String::new();
+            Simple::write(&mut report, info).expect("To write");
+            JustError::write(&mut report, info).expect("To write");
+            report
+        });
+        assert!(report.starts_with("Panic: src/hook/test.rs:"), "{}", report);
+        assert!(report.ends_with(" - lolka 1\nlolka 1\n"), "{}", report);
+    }
+}
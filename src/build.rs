@@ -0,0 +1,34 @@
+//! Build script helpers.
+//!
+//!Add `lazy-panic` to `[build-dependencies]` and call [emit](fn.emit.html) from `build.rs`
+//!to make build information available for [build_info!](../macro.build_info.html)
+//!
+//!```rust,no_run
+//!extern crate lazy_panic;
+//!
+//!fn main() {
+//!    lazy_panic::build::emit();
+//!}
+//!```
+
+use std::env;
+use std::process::Command;
+
+///Emits compilation profile, opt-level and rustc version as environment variables of the crate.
+///
+///Must be called from build script.
+pub fn emit() {
+    let profile = env::var("PROFILE").unwrap_or_else(|_| "unknown".to_owned());
+    let opt_level = env::var("OPT_LEVEL").unwrap_or_else(|_| "unknown".to_owned());
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let rustc = Command::new(rustc).arg("--version")
+                                   .output()
+                                   .ok()
+                                   .and_then(|output| String::from_utf8(output.stdout).ok())
+                                   .map(|version| version.trim().to_owned())
+                                   .unwrap_or_else(|| "unknown".to_owned());
+
+    println!("cargo:rustc-env=LAZY_PANIC_BUILD_PROFILE={}", profile);
+    println!("cargo:rustc-env=LAZY_PANIC_BUILD_OPT_LEVEL={}", opt_level);
+    println!("cargo:rustc-env=LAZY_PANIC_BUILD_RUSTC={}", rustc);
+}
@@ -0,0 +1,194 @@
+//! Runtime configuration of panic hook.
+//!
+//!For applications that read their logging configuration from files,
+//!instead of choosing format type at compile time.
+//!
+//!```rust
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::PanicConfig;
+//!
+//!use std::io;
+//!
+//!fn main() {
+//!    PanicConfig::new().prefix("[my-app] ")
+//!                      .backtrace(true)
+//!                      .writer(io::stdout)
+//!                      .install();
+//!}
+//!```
+
+use std::io;
+use std::process;
+use std::sync::RwLock;
+use PanicHookInfo;
+
+use formatter::{Backtrace, Debug, PanicFormat, Prefix, Simple};
+use hook;
+
+type MakeWriter = Box<dyn Fn() -> Box<dyn io::Write> + Send + Sync>;
+
+#[derive(Clone, Copy)]
+enum OnPanic {
+    Continue,
+    Exit(i32),
+    Abort,
+}
+
+static CONFIG: RwLock<Option<PanicConfig>> = RwLock::new(None);
+
+///Configuration of panic hook, built at runtime.
+///
+///Panic is written as `{backtrace}{prefix}{file}:{line}:{column} - {payload}{suffix}`,
+///where location, payload and suffix are the same as [Simple](../formatter/struct.Simple.html)
+///
+///By default prefix is `Panic: `, backtrace is omitted, stderr is used as writer and
+///execution continues after panic is written (i.e. unwinding or abort, depending on build).
+pub struct PanicConfig {
+    prefix: String,
+    backtrace: bool,
+    writer: Option<MakeWriter>,
+    on_panic: OnPanic,
+}
+
+impl PanicConfig {
+    ///Creates default configuration.
+    pub fn new() -> Self {
+        PanicConfig {
+            prefix: "Panic: ".to_owned(),
+            backtrace: false,
+            writer: None,
+            on_panic: OnPanic::Continue,
+        }
+    }
+
+    ///Sets text written before panic's location.
+    pub fn prefix<T: Into<String>>(mut self, prefix: T) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    ///Sets whether to write backtrace, as [Debug](../formatter/struct.Debug.html) does.
+    ///
+    ///Backtrace is written only with `backtrace-on` feature.
+    pub fn backtrace(mut self, backtrace: bool) -> Self {
+        self.backtrace = backtrace;
+        self
+    }
+
+    ///Sets factory of writer, invoked on each panic.
+    pub fn writer<W: io::Write + 'static, F: Fn() -> W + Send + Sync + 'static>(mut self, writer: F) -> Self {
+        self.writer = Some(Box::new(move || Box::new(writer())));
+        self
+    }
+
+    ///Exits process with `code` after panic is written.
+    pub fn exit(mut self, code: i32) -> Self {
+        self.on_panic = OnPanic::Exit(code);
+        self
+    }
+
+    ///Aborts process after panic is written.
+    pub fn abort(mut self) -> Self {
+        self.on_panic = OnPanic::Abort;
+        self
+    }
+
+    ///Installs panic hook with this configuration.
+    ///
+    ///Replaces configuration of previous call, if any.
+    pub fn install(self) {
+        *CONFIG.write().unwrap_or_else(|error| error.into_inner()) = Some(self);
+        hook::install::<Configured>();
+    }
+}
+
+impl Default for PanicConfig {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//Uses default configuration, if nothing is installed.
+fn with_config<R, F: FnOnce(&PanicConfig) -> R>(closure: F) -> R {
+    let config = CONFIG.read().unwrap_or_else(|error| error.into_inner());
+    match config.as_ref() {
+        Some(config) => closure(config),
+        None => closure(&PanicConfig::new()),
+    }
+}
+
+struct Report;
+
+impl Prefix for Report {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        with_config(|config| writer.write_all(config.prefix.as_bytes()))
+    }
+}
+
+impl Backtrace for Report {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match with_config(|config| config.backtrace) {
+            true => Debug::write_in(writer),
+            false => Ok(()),
+        }
+    }
+}
+
+impl PanicFormat for Report {
+    type Writer = Box<dyn io::Write>;
+    type Backtrace = Self;
+    type Prefix = Self;
+    type PanicInfo = Simple;
+    type Suffix = Simple;
+
+    fn writer() -> Self::Writer {
+        with_config(|config| match config.writer {
+            Some(ref writer) => writer(),
+            None => Box::new(io::BufWriter::new(io::stderr())),
+        })
+    }
+}
+
+struct Configured;
+
+impl PanicFormat for Configured {
+    type Writer = Box<dyn io::Write>;
+    type Backtrace = Report;
+    type Prefix = Report;
+    type PanicInfo = Simple;
+    type Suffix = Simple;
+
+    #[inline]
+    fn writer() -> Self::Writer {
+        Report::writer()
+    }
+
+    fn print(info: &PanicHookInfo) {
+        Report::print(info);
+
+        match with_config(|config| config.on_panic) {
+            OnPanic::Continue => (),
+            OnPanic::Exit(code) => process::exit(code),
+            OnPanic::Abort => process::abort(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::format_panic;
+    use super::{PanicConfig, Configured, CONFIG};
+
+    #[test]
+    fn should_write_configured_prefix() {
+        *CONFIG.write().unwrap() = Some(PanicConfig::new().prefix("[my-app] ").exit(70));
+
+        let report = format_panic::<Configured, _>("lolka");
+        assert!(report.starts_with("[my-app] src/hook/test.rs:"), "{}", report);
+        assert!(report.contains(" - lolka"), "{}", report);
+    }
+}
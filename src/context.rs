@@ -0,0 +1,66 @@
+//! Thread local context of panic reports.
+//!
+//!Use [context!](../macro.context.html) to describe what current thread is doing.
+
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+
+thread_local!(static STACK: RefCell<Vec<(u64, String)>> = const { RefCell::new(Vec::new()) });
+thread_local!(static NEXT_ID: Cell<u64> = const { Cell::new(0) });
+
+///Guard of context entry.
+///
+///Entry is removed from thread's context on drop, even if guards are dropped out of order.
+#[must_use = "Context entry is removed once guard is dropped"]
+pub struct Guard {
+    id: u64,
+    //Guard must be dropped on the same thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let id = self.id;
+        let _ = STACK.try_with(|stack| stack.borrow_mut().retain(|entry| entry.0 != id));
+    }
+}
+
+///Pushes entry into current thread's context.
+///
+///Prefer to use [context!](../macro.context.html)
+pub fn push<T: Into<String>>(entry: T) -> Guard {
+    let entry = entry.into();
+    let id = NEXT_ID.with(|next| next.replace(next.get().wrapping_add(1)));
+    STACK.with(|stack| stack.borrow_mut().push((id, entry)));
+
+    Guard {
+        id,
+        _thread: PhantomData,
+    }
+}
+
+///Returns current thread's context, starting with outermost entry.
+pub fn entries() -> Vec<String> {
+    STACK.try_with(|stack| stack.try_borrow().map(|stack| stack.iter().map(|entry| entry.1.clone()).collect()).unwrap_or_default()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{entries, push};
+
+    #[test]
+    fn should_remove_entries_dropped_out_of_order() {
+        let outer = push("outer");
+        let inner = push("inner");
+
+        drop(outer);
+        assert_eq!(entries(), ["inner"]);
+
+        let innermost = push("innermost");
+        drop(inner);
+        assert_eq!(entries(), ["innermost"]);
+
+        drop(innermost);
+        assert!(entries().is_empty());
+    }
+}
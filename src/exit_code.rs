@@ -0,0 +1,75 @@
+//! Mapping of panics to process exit codes.
+//!
+//!Used by [ExitMapped](../formatter/struct.ExitMapped.html) to choose exit code after panic is printed.
+
+use std::any::{Any, TypeId};
+use std::sync::RwLock;
+use PanicHookInfo;
+
+///Exit code used when no mapping matches, same as Rust's exit code of panicked process.
+pub const DEFAULT: i32 = 101;
+
+type Classifier = Box<dyn Fn(&PanicHookInfo) -> Option<i32> + Send + Sync>;
+
+static CODES: RwLock<Vec<(TypeId, i32)>> = RwLock::new(Vec::new());
+static CLASSIFIER: RwLock<Option<Classifier>> = RwLock::new(None);
+
+///Maps payload's type `T` to exit code.
+///
+///Registering the same type again replaces its previous code.
+pub fn register<T: Any>(code: i32) {
+    let type_id = TypeId::of::<T>();
+    let mut codes = CODES.write().unwrap_or_else(|error| error.into_inner());
+
+    match codes.iter_mut().find(|entry| entry.0 == type_id) {
+        Some(entry) => entry.1 = code,
+        None => codes.push((type_id, code)),
+    }
+}
+
+///Sets classifier callback that chooses exit code.
+///
+///Classifier takes priority over mapping by payload's type.
+///Returning `None` falls back to the mapping.
+///
+///Note that classifier is called inside panic hook, hence it must not panic.
+pub fn set_classifier<F: Fn(&PanicHookInfo) -> Option<i32> + Send + Sync + 'static>(classifier: F) {
+    *CLASSIFIER.write().unwrap_or_else(|error| error.into_inner()) = Some(Box::new(classifier));
+}
+
+///Returns exit code for panic.
+///
+///Returns [DEFAULT](constant.DEFAULT.html) if neither classifier nor mapping by payload's type matches.
+pub fn code(info: &PanicHookInfo) -> i32 {
+    let classifier = CLASSIFIER.read().unwrap_or_else(|error| error.into_inner());
+    if let Some(code) = classifier.as_ref().and_then(|classifier| classifier(info)) {
+        return code;
+    }
+
+    let type_id = info.payload().type_id();
+    let codes = CODES.read().unwrap_or_else(|error| error.into_inner());
+    codes.iter().find(|entry| entry.0 == type_id).map(|entry| entry.1).unwrap_or(DEFAULT)
+}
+
+#[cfg(test)]
+mod tests {
+    use test::with_panic_info;
+    use super::{register, code, DEFAULT};
+
+    struct Registered;
+    struct Replaced;
+    struct Unregistered;
+
+    #[test]
+    fn should_map_payload_to_code() {
+        register::<Registered>(78);
+        register::<Replaced>(1);
+        register::<Replaced>(2);
+
+        assert_eq!(with_panic_info(Registered, code), 78);
+        assert_eq!(with_panic_info(Replaced, code), 2);
+        assert_eq!(with_panic_info(Unregistered, code), DEFAULT);
+        assert_eq!(with_panic_info("lolka", code), DEFAULT);
+        assert_eq!(with_panic_info("lolka".to_owned(), code), DEFAULT);
+    }
+}
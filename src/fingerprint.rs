@@ -0,0 +1,100 @@
+//! Stable fingerprint of panics.
+//!
+//!Fingerprint is computed from panic's location and payload's full text with all numbers replaced,
+//!so that the same panic gets the same fingerprint across runs and hosts.
+
+use PanicHookInfo;
+
+use payload;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+///Computes fingerprint of panic.
+///
+///Payload is taken before redaction and truncation, see [payload::to_raw_string](../payload/fn.to_raw_string.html)
+pub fn fingerprint(info: &PanicHookInfo) -> u64 {
+    let mut hash = Fnv(FNV_OFFSET);
+
+    match info.location() {
+        Some(location) => {
+            hash.write(location.file().as_bytes());
+            hash.write(&location.line().to_le_bytes());
+        },
+        None => hash.write(b"unknown"),
+    }
+
+    hash.write(normalize(&payload::to_raw_string(info.payload())).as_bytes());
+
+    hash.0
+}
+
+//Replaces numbers, including hexadecimal ones, with `#`
+fn normalize(payload: &str) -> String {
+    let mut result = String::with_capacity(payload.len());
+    let mut chars = payload.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if !ch.is_ascii_digit() {
+            result.push(ch);
+            continue;
+        }
+
+        if ch == '0' && chars.peek() == Some(&'x') {
+            chars.next();
+            while chars.peek().map(|ch| ch.is_ascii_hexdigit()).unwrap_or(false) {
+                chars.next();
+            }
+        } else {
+            while chars.peek().map(|ch| ch.is_ascii_digit()).unwrap_or(false) {
+                chars.next();
+            }
+        }
+
+        result.push('#');
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::io;
+
+    use formatter::{Composite, Empty, Fingerprint};
+    use test::format_panic;
+    use super::normalize;
+
+    type FingerprintFormat = Composite<Empty, Fingerprint, Empty, Empty, io::Sink>;
+
+    #[test]
+    fn should_normalize_numbers() {
+        assert_eq!(normalize("index out of bounds: the len is 3 but the index is 10"), "index out of bounds: the len is # but the index is #");
+        assert_eq!(normalize("bad pointer 0xdeadBEEF"), "bad pointer #");
+        assert_eq!(normalize("no numbers"), "no numbers");
+    }
+
+    #[test]
+    fn should_fingerprint_full_payload() {
+        let lolka = format_panic::<FingerprintFormat, _>("fingerprint lolka 1");
+        assert_eq!(format_panic::<FingerprintFormat, _>("fingerprint lolka 2".to_owned()), lolka);
+        assert_eq!(format_panic::<FingerprintFormat, _>(Box::<str>::from("fingerprint lolka 3")), lolka);
+        assert_eq!(format_panic::<FingerprintFormat, _>(Cow::<str>::from("fingerprint lolka 4")), lolka);
+
+        assert_ne!(format_panic::<FingerprintFormat, _>(Box::<str>::from("fingerprint kek")), lolka);
+        assert_ne!(format_panic::<FingerprintFormat, _>(Box::<str>::from("fingerprint kek")), format_panic::<FingerprintFormat, _>(Box::<str>::from("fingerprint cheburek")));
+    }
+}
@@ -3,29 +3,166 @@
 #[cfg(feature = "backtrace-on")]
 extern crate backtrace;
 
-use std::panic;
+use std::cell::Cell;
+use std::fmt;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use PanicHookInfo;
+
+use location;
+use locale::{self, Key};
+#[cfg(feature = "backtrace-on")]
+use origin;
+
+mod time;
+mod thread;
+mod process;
+mod system;
+mod app;
+mod wrapper;
+mod on_error;
+mod pretty;
+mod color;
+mod severity;
+mod wrap;
+mod verbosity;
+mod issue;
+mod composite;
+mod text;
+mod func;
+mod adapter;
+mod shared;
+mod display;
+mod dynamic;
+mod tuple;
+mod fields;
+#[cfg(feature = "log")]
+mod logger;
+#[cfg(feature = "tracing")]
+mod tracing_event;
+#[cfg(feature = "slog")]
+mod slog_target;
+#[cfg(feature = "defmt")]
+mod defmt_target;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod os_log;
+pub mod escape;
+pub mod testkit;
+
+pub use self::time::{Timestamp, EpochTimestamp, Uptime};
+pub use self::thread::{Thread, Context};
+pub use self::process::{Pid, Counter, Env, EnvList, Args, ArgsRedaction, NoRedaction, Memory};
+pub use self::system::{Host, SystemInfo, MachineId};
+pub use self::app::{AppInfo, BuildId, BuildInfo, Metadata, Fingerprint, Runbook};
+pub use self::wrapper::{Abort, Exit, ExitMapped, WithDefault, CoreDump, CORE_DUMP_ENV, Notify, Deterministic};
+pub use self::wrapper::{WithPrefix, WithTimestamp, WithThread, WithPid};
+pub use self::on_error::{OnWriteError, Ignore, FallbackStderr, AbortOnError};
+pub use self::pretty::Pretty;
+pub use self::color::{is_color_enabled, NO_COLOR_ENV, CLICOLOR_FORCE_ENV};
+pub use self::severity::{Severity, SEVERITY_ICON, SEVERITY_ASCII};
+pub use self::wrap::{Wrapped, WrapWriter, terminal_width, INDENT};
+pub use self::verbosity::{Leveled, set_verbosity, verbosity, VERBOSITY_ENV, DEFAULT_VERBOSITY};
+#[cfg(feature = "derive")]
+pub use lazy_panic_macros::PanicFormat;
+pub use self::issue::{IssueUrl, Repository};
+pub use self::composite::{Composite, MakeWriter, StderrWriter, StdoutWriter};
+pub use self::text::{Text, ConstPrefix, ConstSuffix};
+pub use self::func::{WriteFn, FnPrefix, FnSuffix};
+pub use self::adapter::{FmtWriter, IoWriter};
+pub use self::shared::SharedWriter;
+pub use self::display::DisplayPanic;
+pub use self::dynamic::DynPanicFormat;
+pub use self::fields::{Fields, KeyValue, Json};
+#[cfg(feature = "log")]
+pub use self::logger::{LogTarget, LogWriter};
+#[cfg(feature = "tracing")]
+pub use self::tracing_event::TracingEvent;
+#[cfg(feature = "slog")]
+pub use self::slog_target::SlogTarget;
+#[cfg(feature = "defmt")]
+pub use self::defmt_target::DefmtTarget;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub use self::os_log::{OsLog, OsLogWriter};
+
+static DETERMINISTIC: AtomicBool = AtomicBool::new(false);
+
+thread_local!(static THREAD_DETERMINISTIC: Cell<bool> = const { Cell::new(false) });
+
+///Sets whether built-in components omit nondeterministic parts of output.
+///
+///Timestamps, uptime, process and thread ids, user and host names, memory usage, OS version,
+///machine ids, panic counter and backtrace addresses are written as placeholders (e.g. `<pid>`),
+///while absolute paths are shortened to `{crate}/src/{file}`.
+///So that output can be snapshot-tested across machines.
+///
+///See [Deterministic](struct.Deterministic.html) to enable it for single format.
+pub fn set_deterministic(enabled: bool) {
+    DETERMINISTIC.store(enabled, Ordering::Release);
+}
+
+///Returns whether built-in components omit nondeterministic parts of output.
+pub fn is_deterministic() -> bool {
+    DETERMINISTIC.load(Ordering::Acquire) || THREAD_DETERMINISTIC.try_with(|enabled| enabled.get()).unwrap_or(false)
+}
 
 ///Describes how to write panic's message prefix.
 ///
 ///Generally should be simple prefix that will go as `{Prefix}{PanicInfo}...`
+///
+///Implement `write_in_with` instead of `write_in` when prefix depends on panic (e.g. payload's type).
+///
+///Tuple of up to 8 prefixes writes each of them in order, e.g. `(Timestamp, Thread)`
 pub trait Prefix {
-    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()>;
+    ///Writes prefix, that doesn't depend on panic.
+    ///
+    ///Writes nothing by default.
+    #[inline]
+    fn write_in<W: io::Write>(_writer: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    ///Writes prefix of particular panic.
+    ///
+    ///Uses `write_in` by default.
+    #[inline]
+    fn write_in_with<W: io::Write>(writer: &mut W, _info: &PanicHookInfo) -> io::Result<()> {
+        Self::write_in(writer)
+    }
 }
 
 ///Describes how to write `PanicInfo`
 pub trait PanicInfo {
-    fn write_in<W: io::Write>(writer: &mut W, info: &panic::PanicInfo) -> io::Result<()>;
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()>;
 }
 
 ///Describes how to write panic's message suffix.
 ///
 ///Generally should be simple suffix that will go as `...{PanicInfo}{Suffix}`
+///
+///Implement `write_in_with` instead of `write_in` when suffix depends on panic.
+///
+///Tuple of up to 8 suffixes writes each of them in order, e.g. `(BuildId, SystemInfo)`
 pub trait Suffix {
-    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()>;
+    ///Writes suffix, that doesn't depend on panic.
+    ///
+    ///Writes nothing by default.
+    #[inline]
+    fn write_in<W: io::Write>(_writer: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    ///Writes suffix of particular panic.
+    ///
+    ///Uses `write_in` by default.
+    #[inline]
+    fn write_in_with<W: io::Write>(writer: &mut W, _info: &PanicHookInfo) -> io::Result<()> {
+        Self::write_in(writer)
+    }
 }
 
 ///Describes how to write panic's backtrace
+///
+///Tuple of up to 8 backtraces writes each of them in order.
 pub trait Backtrace {
     fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()>;
 }
@@ -46,7 +183,7 @@ impl Prefix for Empty {
 
 impl PanicInfo for Empty {
     #[inline]
-    fn write_in<W: io::Write>(_: &mut W, _: &panic::PanicInfo) -> io::Result<()> {
+    fn write_in<W: io::Write>(_: &mut W, _: &PanicHookInfo) -> io::Result<()> {
         Ok(())
     }
 }
@@ -67,11 +204,12 @@ impl Backtrace for Empty {
 
 ///Simple configuration that should be generic.
 ///
-///For prefix it is constant string `Panic: `
+///For prefix it is string `Panic: `, translated via [locale](../locale/index.html)
 ///
-///For `PanicInfo` it writes `{file}:{line} - {payload}`
+///For `PanicInfo` it writes `{file}:{line}:{column} - {payload}`, with file's prefix stripped
+///according to [location](../location/index.html) configuration
 ///
-///For suffix it is `\n`
+///For suffix it is [Metadata](struct.Metadata.html) and [Context](struct.Context.html) followed by `\n`
 ///
 ///For backtrace it is noop
 ///
@@ -81,25 +219,27 @@ pub struct Simple;
 impl Prefix for Simple {
     #[inline]
     fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
-        writer.write_all("Panic: ".as_bytes())
+        write!(writer, "{}: ", locale::text(Key::Panic))
     }
 }
 
 impl PanicInfo for Simple {
     #[inline]
-    fn write_in<W: io::Write>(writer: &mut W, info: &panic::PanicInfo) -> io::Result<()> {
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
         match info.location() {
-            Some(location) => write!(writer, "{}:{} - ", location.file(), location.line()),
-            None  => write!(writer, "unknown:0 - ")
+            Some(location) => write!(writer, "{}:{}:{} - ", location::strip(location.file()), location.line(), location.column()),
+            None  => write!(writer, "unknown:0:0 - ")
         }?;
-        write_payload!(writer, info.payload(), types: [&str, String])
+        write_payload_default!(writer, info.payload())
     }
 }
 
 impl Suffix for Simple {
     #[inline]
     fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
-        write!(writer, "\n")
+        Metadata::write_in(writer)?;
+        Context::write_in(writer)?;
+        writeln!(writer)
     }
 }
 
@@ -110,6 +250,28 @@ impl Backtrace for Simple {
     }
 }
 
+///Section of panic's report, written by [PanicFormat](trait.PanicFormat.html)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    ///Writes `Backtrace`
+    Backtrace,
+    ///Writes `Prefix`
+    Prefix,
+    ///Writes `PanicInfo`
+    PanicInfo,
+    ///Writes `Suffix`
+    Suffix,
+    ///Flushes writer, so that preceding sections are not lost if following ones fail.
+    Flush,
+}
+
+impl Section {
+    ///Default order of sections.
+    pub const DEFAULT: &'static [Section] = &[Section::Backtrace, Section::Prefix, Section::PanicInfo, Section::Suffix];
+    ///Order of sections with `TWO_STAGE` enabled.
+    pub const TWO_STAGE: &'static [Section] = &[Section::Prefix, Section::PanicInfo, Section::Flush, Section::Suffix, Section::Backtrace];
+}
+
 ///Panic formatter
 ///
 ///Default print method writes each component in following order:
@@ -117,6 +279,14 @@ impl Backtrace for Simple {
 ///2. Prefix
 ///3. `PanicInfo`
 ///5. Suffix
+///
+///Then writer is passed to `finalize`.
+///
+///With `TWO_STAGE` enabled, header made of Prefix and `PanicInfo` is flushed first,
+///followed by Suffix and Backtrace.
+///So that panic's message is not lost if backtrace's symbolication crashes or hangs.
+///
+///Order can be overridden via `SECTIONS`, omitting any of sections.
 pub trait PanicFormat {
     type Writer: io::Write;
     type Backtrace: Backtrace;
@@ -124,15 +294,95 @@ pub trait PanicFormat {
     type PanicInfo: PanicInfo;
     type Suffix: Suffix;
 
+    ///Whether to flush header before writing the rest.
+    const TWO_STAGE: bool = false;
+
+    ///Sections to write in order.
+    ///
+    ///[Section::TWO_STAGE](enum.Section.html#associatedconstant.TWO_STAGE) with `TWO_STAGE` enabled,
+    ///otherwise [Section::DEFAULT](enum.Section.html#associatedconstant.DEFAULT)
+    const SECTIONS: &'static [Section] = match Self::TWO_STAGE {
+        true => Section::TWO_STAGE,
+        false => Section::DEFAULT,
+    };
+
     fn writer() -> Self::Writer;
 
-    fn print(info: &panic::PanicInfo) {
+    ///Handles first error of writer, after all components are written.
+    ///
+    ///Ignores error by default.
+    ///Override to pick another strategy, e.g. `FallbackStderr::on_write_error(info, error)`
+    #[inline]
+    fn on_write_error(info: &PanicHookInfo, error: io::Error) {
+        Ignore::on_write_error(info, error)
+    }
+
+    ///Completes transmission once all components are written.
+    ///
+    ///Flushes writer by default.
+    ///Override for writers that require explicit commit (e.g. send datagram or fsync file).
+    #[inline]
+    fn finalize(mut writer: Self::Writer) -> io::Result<()> {
+        io::Write::flush(&mut writer)
+    }
+
+    ///Writes each component into `writer` in order of `SECTIONS`.
+    ///
+    ///Every component is attempted, even if previous one failed, and the first error is returned.
+    fn write_report<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        let mut result = Ok(());
+        for section in Self::SECTIONS {
+            result = result.and(match section {
+                Section::Backtrace => Self::Backtrace::write_in(writer),
+                Section::Prefix => Self::Prefix::write_in_with(writer, info),
+                Section::PanicInfo => Self::PanicInfo::write_in(writer, info),
+                Section::Suffix => Self::Suffix::write_in_with(writer, info),
+                Section::Flush => writer.flush(),
+            });
+        }
+        result
+    }
+
+    ///Writes panic into `writer` held by caller (e.g. TCP stream), instead of own one.
+    ///
+    ///Writer is flushed afterwards, while `finalize` is not invoked.
+    ///Error is handled via `on_write_error`, same as `print` does.
+    fn print_to<W: io::Write>(info: &PanicHookInfo, writer: &mut W) {
+        let result = Self::write_report(writer, info).and(writer.flush());
+
+        if let Err(error) = result {
+            Self::on_write_error(info, error);
+        }
+    }
+
+    ///Writes each component into `fmt::Write` (e.g. `String`) in configured order.
+    ///
+    ///Output is adapted via [FmtWriter](struct.FmtWriter.html)
+    fn write_fmt_report<W: fmt::Write + ?Sized>(writer: &mut W, info: &PanicHookInfo) -> fmt::Result {
+        let mut writer = FmtWriter::new(writer);
+        Self::write_report(&mut writer, info).and(io::Write::flush(&mut writer)).map_err(|_| fmt::Error)
+    }
+
+    ///Returns report, as written by components, in `String`.
+    ///
+    ///So that applications which already catch panics can propagate it as error.
+    ///Errors of components are ignored.
+    fn format_to_string(info: &PanicHookInfo) -> String {
+        let mut report = Vec::new();
+        let _ = Self::write_report(&mut report, info);
+        String::from_utf8_lossy(&report).into_owned()
+    }
+
+    fn print(info: &PanicHookInfo) {
         let mut writer = Self::writer();
 
-        let _ = Self::Backtrace::write_in(&mut writer);
-        let _ = Self::Prefix::write_in(&mut writer);
-        let _ = Self::PanicInfo::write_in(&mut writer, info);
-        let _ = Self::Suffix::write_in(&mut writer);
+        let result = Self::write_report(&mut writer, info);
+        //Finalize explicitly, as process might be aborted right after.
+        let result = result.and(Self::finalize(writer));
+
+        if let Err(error) = result {
+            Self::on_write_error(info, error);
+        }
     }
 }
 
@@ -160,7 +410,7 @@ impl PanicFormat for Empty {
         io::stderr()
     }
 
-    fn print(_: &panic::PanicInfo) {
+    fn print(_: &PanicHookInfo) {
     }
 }
 
@@ -174,59 +424,95 @@ impl PanicFormat for Empty {
 ///So actual call stack may be longer
 pub struct Debug;
 
-impl Backtrace for Debug {
+impl Debug {
+    ///Writes backtrace as [Backtrace](trait.Backtrace.html) does, except that header is always `Stack backtrace:`
+    ///
+    ///For structured output (e.g. JSON report), which must not depend on [locale](../locale/index.html)
+    #[inline]
+    pub fn write_untranslated<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        Self::write_with_header(writer, Key::StackBacktrace.default_text())
+    }
+
     #[cfg(not(feature = "backtrace-on"))]
     #[inline]
-    fn write_in<W: io::Write>(_: &mut W) -> io::Result<()> {
+    fn write_with_header<W: io::Write>(_: &mut W, _: &str) -> io::Result<()> {
         Ok(())
     }
 
     #[cfg(feature = "backtrace-on")]
+    fn write_with_header<W: io::Write>(writer: &mut W, header: &str) -> io::Result<()> {
+        write!(writer, "{}:", header)?;
+        write_backtrace(writer)?;
+        writeln!(writer)
+    }
+}
+
+impl Backtrace for Debug {
     #[inline]
     fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
-        use std::mem;
-
-        //First 3 frames are from backtrace.
-        //In middle 3 are from lazy_panic
-        //Last 2 are from Rust runtime
-        const TRASH_FRAMES_NUM: usize = 8;
-        const HEX_WIDTH: usize = mem::size_of::<usize>() + 2;
-
-        let backtrace = self::backtrace::Backtrace::new();
-        //By default backtrace includes last function call
-        //which means the above new()
-        //But we should really trim it down to user panic
-
-        //Code is based on backtrace source
-        write!(writer, "Stack backtrace:")?;
-        for (idx, frame) in backtrace.frames().iter().skip(TRASH_FRAMES_NUM).enumerate() {
-            let ip = frame.ip();
-            write!(writer, "\n{:4}: {:2$?}", idx, ip, HEX_WIDTH)?;
-
-            let symbols = frame.symbols();
-            if symbols.len() == 0 {
-                write!(writer, " - <unresolved>")?;
-            }
+        Self::write_with_header(writer, &locale::text(Key::StackBacktrace))
+    }
+}
 
-            for (idx, symbol) in symbols.iter().enumerate() {
-                if idx != 0 {
-                    write!(writer, "\n      {:1$}", "", HEX_WIDTH)?;
-                }
+//Writes frames of backtrace, starting from user's code.
+#[cfg(feature = "backtrace-on")]
+fn write_backtrace<W: io::Write>(writer: &mut W) -> io::Result<()> {
+    use std::mem;
+
+    //Used when runtime's frames cannot be recognized (e.g. no symbols).
+    //First 3 frames are from backtrace.
+    //In middle are from lazy_panic
+    //Last 2 are from Rust runtime
+    const TRASH_FRAMES_NUM: usize = 16;
+    const HEX_WIDTH: usize = mem::size_of::<usize>() + 2;
+
+    let backtrace = origin::backtrace();
+    //By default backtrace includes last function call
+    //which means the above new()
+    //But we should really trim it down to user panic
+    let frames = backtrace.frames();
+    let is_runtime = |frame: &self::backtrace::BacktraceFrame, marker: &str| frame.symbols().iter().any(|symbol| match symbol.name() {
+        Some(name) => format!("{:#}", name).contains(marker),
+        None => false,
+    });
+    let skip = match frames.iter().rposition(|frame| is_runtime(frame, "__rust_end_short_backtrace")) {
+        Some(marker) => frames[marker + 1..].iter()
+                                            .take_while(|frame| is_runtime(frame, "std::panicking::") || is_runtime(frame, "core::panicking::"))
+                                            .count() + marker + 1,
+        None => TRASH_FRAMES_NUM,
+    };
+
+    //Code is based on backtrace source
+    for (idx, frame) in frames.iter().skip(skip).enumerate() {
+        match is_deterministic() {
+            true => write!(writer, "\n{:4}: {:2$}", idx, "<addr>", HEX_WIDTH),
+            false => write!(writer, "\n{:4}: {:2$?}", idx, frame.ip(), HEX_WIDTH),
+        }?;
 
-                if let Some(name) = symbol.name() {
-                    write!(writer, " - {}", name)?;
-                } else {
-                    write!(writer, " - <unknown>")?;
-                }
+        let symbols = frame.symbols();
+        if symbols.is_empty() {
+            write!(writer, " - <unresolved>")?;
+        }
 
-                if let (Some(file), Some(line)) = (symbol.filename(), symbol.lineno()) {
-                    write!(writer, "\n      {:3$}at {}:{}", "", file.display(), line, HEX_WIDTH)?;
-                }
+        for (idx, symbol) in symbols.iter().enumerate() {
+            if idx != 0 {
+                write!(writer, "\n      {:1$}", "", HEX_WIDTH)?;
             }
-        }
 
-        write!(writer, "\n")
+            if let Some(name) = symbol.name() {
+                write!(writer, " - {}", name)?;
+            } else {
+                write!(writer, " - <unknown>")?;
+            }
+
+            if let (Some(file), Some(line)) = (symbol.filename(), symbol.lineno()) {
+                let file = file.to_string_lossy();
+                write!(writer, "\n      {:3$}at {}:{}", "", location::strip(&file), line, HEX_WIDTH)?;
+            }
+        }
     }
+
+    Ok(())
 }
 
 impl PanicFormat for Debug {
@@ -249,8 +535,8 @@ pub struct JustError;
 
 impl PanicInfo for JustError {
     #[inline]
-    fn write_in<W: io::Write>(writer: &mut W, info: &panic::PanicInfo) -> io::Result<()> {
-        write_payload!(writer, info.payload(), types: [&str, String])
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        write_payload_default!(writer, info.payload())
     }
 }
 
@@ -269,7 +555,14 @@ impl PanicFormat for JustError {
 
 #[cfg(test)]
 mod tests {
-    use super::{Simple, Empty, Debug, JustError};
+    use std::any::Any;
+    use std::borrow::Cow;
+    use std::io;
+    use std::thread;
+    
+    use hook::set_thread_format;
+    use test::{captured_by, format_panic, with_panic_info, Captured};
+    use super::{PanicFormat, Prefix, Suffix, Section, Simple, Empty, Debug, JustError, WithDefault, Pretty, Deterministic};
 
     #[test]
     #[should_panic]
@@ -299,4 +592,240 @@ mod tests {
         panic!("lolka");
     }
 
+    #[test]
+    #[should_panic]
+    fn should_build_dependent_panic() {
+        set_panic_message!(debug: Debug, release: JustError);
+        panic!("lolka");
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_with_default_panic() {
+        set_panic_message!(WithDefault<Simple>);
+        panic!("lolka");
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_pretty_panic() {
+        set_panic_message!(Pretty);
+        panic!("lolka");
+    }
+
+    struct TwoStage;
+
+    impl PanicFormat for TwoStage {
+        type Writer = io::BufWriter<io::Stderr>;
+        type Backtrace = Debug;
+        type Prefix = Simple;
+        type PanicInfo = Simple;
+        type Suffix = Simple;
+
+        const TWO_STAGE: bool = true;
+
+        fn writer() -> Self::Writer {
+            io::BufWriter::new(io::stderr())
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_two_stage_panic() {
+        set_panic_message!(TwoStage);
+        panic!("lolka");
+    }
+
+    #[test]
+    fn should_finalize_writer() {
+        set_panic_message!(Simple);
+
+        //`CaptureWriter` stores output only once flushed by `finalize`
+        let worker = thread::Builder::new().name("finalize-worker".to_owned()).spawn(|| {
+            set_thread_format::<Captured<Simple>>();
+            panic!("lolka");
+        }).expect("To spawn");
+        assert!(worker.join().is_err());
+
+        let report = captured_by("finalize-worker");
+        assert!(report.contains("- lolka"), "{}", report);
+    }
+
+    #[test]
+    fn should_format_to_string() {
+        assert!(format_panic::<JustError, _>("lolka").starts_with("lolka"));
+    }
+
+    struct Nondeterministic;
+
+    impl PanicFormat for Nondeterministic {
+        type Writer = io::Sink;
+        type Backtrace = Simple;
+        type Prefix = super::Thread;
+        type PanicInfo = Simple;
+        type Suffix = super::SystemInfo;
+
+        fn writer() -> Self::Writer {
+            io::sink()
+        }
+    }
+
+    #[test]
+    fn should_omit_nondeterministic_output() {
+        let report = format_panic::<Deterministic<Nondeterministic>, _>("lolka");
+        assert!(report.starts_with("[thread '"), "{}", report);
+        assert!(report.contains(" (tid <tid>)] src/hook/test.rs:"), "{}", report);
+        assert!(report.contains(" version=<version> "), "{}", report);
+        assert!(!super::is_deterministic());
+    }
+
+    struct PayloadType;
+
+    impl Prefix for PayloadType {
+        fn write_in_with<W: io::Write>(writer: &mut W, info: &::PanicHookInfo) -> io::Result<()> {
+            match info.payload().is::<&str>() {
+                true => writer.write_all(b"[static] "),
+                false => writer.write_all(b"[dynamic] "),
+            }
+        }
+    }
+
+    impl Suffix for PayloadType {
+        fn write_in_with<W: io::Write>(writer: &mut W, info: &::PanicHookInfo) -> io::Result<()> {
+            write!(writer, " ({} bytes)", ::payload::to_string(info.payload()).len())
+        }
+    }
+
+    struct Typed;
+
+    impl PanicFormat for Typed {
+        type Writer = io::Sink;
+        type Backtrace = Empty;
+        type Prefix = PayloadType;
+        type PanicInfo = JustError;
+        type Suffix = PayloadType;
+
+        fn writer() -> Self::Writer {
+            io::sink()
+        }
+    }
+
+    struct Reordered;
+
+    impl PanicFormat for Reordered {
+        type Writer = io::Sink;
+        type Backtrace = Empty;
+        type Prefix = PayloadType;
+        type PanicInfo = JustError;
+        type Suffix = PayloadType;
+
+        const SECTIONS: &'static [Section] = &[Section::PanicInfo, Section::Flush, Section::Prefix];
+
+        fn writer() -> Self::Writer {
+            io::sink()
+        }
+    }
+
+    #[test]
+    fn should_write_sections_in_order() {
+        assert_eq!(Typed::SECTIONS, Section::DEFAULT);
+        assert_eq!(super::Pretty::SECTIONS, Section::TWO_STAGE);
+        assert_eq!(format_panic::<Reordered, _>("lolka"), "lolka[static] ");
+    }
+
+    #[test]
+    fn should_pass_panic_to_prefix_and_suffix() {
+        assert_eq!(format_panic::<Typed, _>("lolka"), "[static] lolka (5 bytes)");
+        assert_eq!(format_panic::<Typed, _>("lolka".to_owned()), "[dynamic] lolka (5 bytes)");
+    }
+
+    define_panic_format!(Fatal { prefix: "fatal: ", info: JustError, suffix: "\n", backtrace: Empty, writer: stderr });
+    define_panic_format!(Defined { prefix: Simple, info: super::Simple, suffix: ">", backtrace: Empty, writer: Vec<u8>, });
+
+    #[test]
+    fn should_define_panic_format() {
+        assert_eq!(format_panic::<Fatal, _>("lolka"), "fatal: lolka\n");
+
+        let report = format_panic::<Defined, _>("lolka");
+        assert!(report.starts_with("Panic: src/hook/test.rs:"), "{}", report);
+        assert!(report.ends_with(" - lolka>"), "{}", report);
+    }
+
+    #[test]
+    fn should_compose_format() {
+        type Composed = super::Composite<super::Pid, JustError, Empty, Empty, Vec<u8>>;
+
+        let report = format_panic::<Composed, _>("lolka");
+        assert!(report.starts_with("[pid "), "{}", report);
+        assert!(report.ends_with("] lolka"), "{}", report);
+        assert!(Composed::writer().is_empty());
+    }
+
+    define_text!(Daemon = "[my-daemon] ");
+    define_text!(End = " <end>");
+
+    #[test]
+    fn should_write_const_text() {
+        type Composed = super::Composite<super::ConstPrefix<Daemon>, JustError, super::ConstSuffix<End>, Empty, Vec<u8>>;
+
+        assert_eq!(format_panic::<Composed, _>("lolka"), "[my-daemon] lolka <end>");
+    }
+
+    #[test]
+    fn should_write_fmt_report() {
+        let report = with_panic_info("lolka", |info| {
+            let mut report = String::from(">");
+            JustError::write_fmt_report(&mut report, info).expect("To write");
+            report.push_str(&format!("task failed: {}", super::DisplayPanic::<JustError>::new(info)));
+            report
+        });
+        assert!(report.starts_with(">lolka"), "{}", report);
+        assert!(report.contains("\ntask failed: lolka"), "{}", report);
+    }
+
+    #[test]
+    fn should_print_to_writer() {
+        //`BufWriter` keeps output unless `print_to` flushes it.
+        let printed = with_panic_info("lolka", |info| {
+            let mut writer = io::BufWriter::new(Vec::new());
+            JustError::print_to(info, &mut writer);
+            String::from_utf8_lossy(writer.get_ref()).into_owned()
+        });
+        assert!(printed.starts_with("lolka"), "{}", printed);
+    }
+
+    #[cfg(feature = "backtrace-on")]
+    #[test]
+    fn should_write_untranslated_backtrace() {
+        use lock;
+        use locale::{self, Key};
+        use super::Backtrace;
+
+        let _lock = lock(&locale::TEST_LOCK);
+        locale::set(Key::StackBacktrace, "Aufrufliste");
+        let mut translated = Vec::new();
+        Debug::write_in(&mut translated).expect("To write");
+        let mut untranslated = Vec::new();
+        Debug::write_untranslated(&mut untranslated).expect("To write");
+        locale::set(Key::StackBacktrace, Key::StackBacktrace.default_text());
+
+        assert!(translated.starts_with(b"Aufrufliste:"));
+        assert!(untranslated.starts_with(b"Stack backtrace:"));
+    }
+
+    #[test]
+    fn should_write_default_payload_types() {
+        let payloads: Vec<Box<dyn Any + Send>> = vec![
+            Box::new("lolka"),
+            Box::new("lolka".to_owned()),
+            Box::new(Cow::Borrowed("lolka")),
+            Box::new(String::from("lolka").into_boxed_str()),
+        ];
+
+        for payload in payloads {
+            let mut buffer = Vec::new();
+            write_payload_default!(&mut buffer, &*payload).expect("To write");
+            assert_eq!(buffer, b"lolka");
+        }
+    }
 }
@@ -0,0 +1,127 @@
+//! Adapters between `io::Write` and `fmt::Write`
+
+use std::fmt;
+use std::io;
+use std::str;
+
+fn fmt_error(_: fmt::Error) -> io::Error {
+    io::Error::other("formatter error")
+}
+
+///Adapts `fmt::Write` (e.g. `String`) to be used as `io::Write`
+///
+///Invalid UTF-8 is written as `U+FFFD`, while incomplete sequence is kept until next write or flush.
+pub struct FmtWriter<'a, W: 'a + ?Sized> {
+    writer: &'a mut W,
+    pending: Vec<u8>,
+}
+
+impl<'a, W: fmt::Write + ?Sized> FmtWriter<'a, W> {
+    ///Creates new instance.
+    pub fn new(writer: &'a mut W) -> Self {
+        FmtWriter {
+            writer,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl<'a, W: fmt::Write + ?Sized> io::Write for FmtWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+
+        loop {
+            let (valid, invalid) = match str::from_utf8(&self.pending) {
+                Ok(text) => (text.len(), None),
+                Err(error) => (error.valid_up_to(), error.error_len()),
+            };
+            //Prefix up to `valid` is checked above.
+            let text = str::from_utf8(&self.pending[..valid]).unwrap_or_default();
+            self.writer.write_str(text).map_err(fmt_error)?;
+
+            match invalid {
+                Some(len) => {
+                    self.writer.write_str("\u{fffd}").map_err(fmt_error)?;
+                    self.pending.drain(..valid + len);
+                },
+                None => {
+                    self.pending.drain(..valid);
+                    break;
+                },
+            }
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.writer.write_str("\u{fffd}").map_err(fmt_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+///Adapts `io::Write` to be used as `fmt::Write`
+///
+///Error of writer is kept, as `fmt::Error` carries no information.
+pub struct IoWriter<'a, W: 'a + ?Sized> {
+    writer: &'a mut W,
+    ///First error of writer, if any.
+    pub error: Option<io::Error>,
+}
+
+impl<'a, W: io::Write + ?Sized> IoWriter<'a, W> {
+    ///Creates new instance.
+    pub fn new(writer: &'a mut W) -> Self {
+        IoWriter {
+            writer,
+            error: None,
+        }
+    }
+}
+
+impl<'a, W: io::Write + ?Sized> fmt::Write for IoWriter<'a, W> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        match self.writer.write_all(text.as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(error) => {
+                self.error.get_or_insert(error);
+                Err(fmt::Error)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Write as FmtWrite;
+    use std::io::Write;
+
+    use super::{FmtWriter, IoWriter};
+
+    #[test]
+    fn should_write_io_into_fmt() {
+        let mut text = String::new();
+        {
+            let mut writer = FmtWriter::new(&mut text);
+            writer.write_all(b"lol \xff").unwrap();
+            writer.write_all(&"ä".as_bytes()[..1]).unwrap();
+            writer.write_all(&"ä".as_bytes()[1..]).unwrap();
+            writer.write_all(&"ä".as_bytes()[..1]).unwrap();
+            writer.flush().unwrap();
+        }
+        assert_eq!(text, "lol \u{fffd}ä\u{fffd}");
+    }
+
+    #[test]
+    fn should_write_fmt_into_io() {
+        let mut buffer = Vec::new();
+        let mut writer = IoWriter::new(&mut buffer);
+        write!(writer, "lolka {}", 1).unwrap();
+        assert!(writer.error.is_none());
+        assert_eq!(buffer, b"lolka 1");
+    }
+}
@@ -0,0 +1,232 @@
+//! Application related components.
+
+use std::io;
+use PanicHookInfo;
+
+use fingerprint;
+use metadata;
+use runbook;
+use super::{Fields, Prefix, PanicInfo, Suffix};
+
+///Writes application's name and version registered by [app_info!](../macro.app_info.html)
+///
+///As [Prefix](trait.Prefix.html) it goes as `[my-app v1.0.0] `
+///
+///As [Fields](trait.Fields.html) it goes as `app` and `version`
+///
+///Writes nothing if application's info is not registered.
+pub struct AppInfo;
+
+impl Prefix for AppInfo {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match metadata::app_info() {
+            Some((name, version)) => write!(writer, "[{} v{}] ", name, version),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Fields for AppInfo {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if let Some((name, version)) = metadata::app_info() {
+            field("app", name);
+            field("version", version);
+        }
+    }
+}
+
+///Writes build identifier registered by [set_build_id](../metadata/fn.set_build_id.html)
+///
+///As [Suffix](trait.Suffix.html) it goes as ` [build=abc123]`
+///
+///As [Fields](trait.Fields.html) it goes as `build`
+///
+///Writes nothing if build identifier is not registered.
+pub struct BuildId;
+
+impl Suffix for BuildId {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match metadata::build_id() {
+            Some(id) => write!(writer, " [build={}]", id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Fields for BuildId {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if let Some(id) = metadata::build_id() {
+            field("build", &id);
+        }
+    }
+}
+
+///Writes build information registered by [build_info!](../macro.build_info.html)
+///
+///As [Suffix](trait.Suffix.html) it goes as `\nBuild: profile=release opt-level=3 rustc="rustc 1.30.0"`
+///
+///As [Fields](trait.Fields.html) it goes as `profile`, `opt_level` and `rustc`
+///
+///Writes nothing if build information is not registered.
+pub struct BuildInfo;
+
+impl Suffix for BuildInfo {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match metadata::build_info() {
+            Some(info) => write!(writer, "\nBuild: profile={} opt-level={} rustc={:?}", info.profile, info.opt_level, info.rustc),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Fields for BuildInfo {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if let Some(info) = metadata::build_info() {
+            field("profile", info.profile);
+            field("opt_level", info.opt_level);
+            field("rustc", info.rustc);
+        }
+    }
+}
+
+///Writes all entries registered in [metadata](../metadata/index.html)
+///
+///As [Suffix](trait.Suffix.html) it goes as ` [build=abc123 region="eu west"]`
+///
+///As [Fields](trait.Fields.html) it goes as each entry
+///
+///Values containing whitespace, quotes or brackets are written quoted.
+///Writes nothing if there are no entries.
+pub struct Metadata;
+
+impl Suffix for Metadata {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let entries = metadata::entries();
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_all(b" [")?;
+        for (idx, (key, value)) in entries.iter().enumerate() {
+            if idx != 0 {
+                writer.write_all(b" ")?;
+            }
+
+            match value.contains(|ch: char| ch.is_whitespace() || ch == '"' || ch == ']') {
+                true => write!(writer, "{}={:?}", key, value),
+                false => write!(writer, "{}={}", key, value),
+            }?;
+        }
+        writer.write_all(b"]")
+    }
+}
+
+impl Fields for Metadata {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        for (key, value) in metadata::entries() {
+            field(&key, &value);
+        }
+    }
+}
+
+///Writes [fingerprint](../fingerprint/index.html) of panic.
+///
+///As [PanicInfo](trait.PanicInfo.html) it goes as `fingerprint=ab12cd34ef567890`
+///
+///Can be combined with other `PanicInfo`:
+///
+///```rust
+///use std::io;
+///use lazy_panic::PanicHookInfo;
+///use lazy_panic::formatter::{PanicInfo, Simple, Fingerprint};
+///
+///pub struct FingerprintedInfo;
+///
+///impl PanicInfo for FingerprintedInfo {
+///    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+///        Simple::write_in(writer, info)?;
+///        writer.write_all(b" ")?;
+///        Fingerprint::write_in(writer, info)
+///    }
+///}
+///```
+pub struct Fingerprint;
+
+impl PanicInfo for Fingerprint {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        write!(writer, "fingerprint={:016x}", fingerprint::fingerprint(info))
+    }
+}
+
+///Writes URL registered for panic in [runbook](../runbook/index.html)
+///
+///As [PanicInfo](trait.PanicInfo.html) it goes as `\nSee: https://wiki.example.com/runbook`
+///
+///Writes nothing if there is no URL for panic.
+pub struct Runbook;
+
+impl PanicInfo for Runbook {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        match runbook::lookup(info) {
+            Some(url) => write!(writer, "\nSee: {}", url),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use metadata;
+    use super::{Prefix, Suffix, AppInfo, BuildId, BuildInfo, Metadata};
+
+    #[test]
+    fn should_write_app_info() {
+        app_info!();
+
+        let mut buffer = Vec::new();
+        AppInfo::write_in(&mut buffer).expect("To write");
+        assert_eq!(buffer, format!("[lazy-panic v{}] ", env!("CARGO_PKG_VERSION")).into_bytes());
+    }
+
+    #[test]
+    fn should_write_build_id() {
+        metadata::set_build_id("abc123");
+
+        let mut buffer = Vec::new();
+        BuildId::write_in(&mut buffer).expect("To write");
+        assert_eq!(buffer, b" [build=abc123]");
+    }
+
+    #[test]
+    fn should_write_build_info() {
+        build_info!();
+
+        let mut buffer = Vec::new();
+        BuildInfo::write_in(&mut buffer).expect("To write");
+        let output = String::from_utf8(buffer).expect("UTF-8");
+
+        assert!(output.starts_with("\nBuild: profile="));
+        assert!(output.ends_with(" opt-level=unknown rustc=\"unknown\""));
+    }
+
+    #[test]
+    fn should_write_metadata() {
+        metadata::insert("region", "eu west");
+
+        let mut buffer = Vec::new();
+        Metadata::write_in(&mut buffer).expect("To write");
+        let output = String::from_utf8(buffer).expect("UTF-8");
+
+        assert!(output.starts_with(" ["));
+        assert!(output.contains("region=\"eu west\""));
+        assert!(output.ends_with("]"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Choice of colored output.
+
+use std::env;
+use std::ffi::OsStr;
+
+///Environment variable disabling colors, when set to non-empty value.
+pub const NO_COLOR_ENV: &str = "NO_COLOR";
+///Environment variable forcing colors, when set to value other than `0`
+///
+///Takes priority over [NO_COLOR_ENV](constant.NO_COLOR_ENV.html)
+pub const CLICOLOR_FORCE_ENV: &str = "CLICOLOR_FORCE";
+
+fn choose(no_color: Option<&OsStr>, force: Option<&OsStr>, is_terminal: bool) -> bool {
+    match force {
+        Some(force) if force != "0" && !force.is_empty() => return true,
+        _ => (),
+    }
+
+    match no_color {
+        Some(no_color) if !no_color.is_empty() => false,
+        _ => is_terminal,
+    }
+}
+
+fn is_stderr_terminal() -> bool {
+    use std::io::{self, IsTerminal};
+
+    io::stderr().is_terminal()
+}
+
+///Returns whether colorized formatters (e.g. [Pretty](struct.Pretty.html)) write ANSI colors.
+///
+///Follows `NO_COLOR` and `CLICOLOR_FORCE` conventions, checked at panic time.
+///Otherwise colors are written only when stderr is terminal, not file or pipe.
+pub fn is_color_enabled() -> bool {
+    let no_color = env::var_os(NO_COLOR_ENV);
+    let force = env::var_os(CLICOLOR_FORCE_ENV);
+    choose(no_color.as_ref().map(AsRef::as_ref), force.as_ref().map(AsRef::as_ref), is_stderr_terminal())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+
+    use super::choose;
+
+    #[test]
+    fn should_choose_color() {
+        assert!(choose(None, None, true));
+        assert!(choose(Some(OsStr::new("")), None, true));
+        assert!(!choose(Some(OsStr::new("1")), None, true));
+        assert!(!choose(Some(OsStr::new("1")), Some(OsStr::new("0")), true));
+        assert!(choose(Some(OsStr::new("1")), Some(OsStr::new("1")), true));
+    }
+
+    #[test]
+    fn should_disable_color_without_terminal() {
+        assert!(!choose(None, None, false));
+        assert!(!choose(None, Some(OsStr::new("0")), false));
+        assert!(choose(Some(OsStr::new("1")), Some(OsStr::new("1")), false));
+    }
+}
@@ -0,0 +1,83 @@
+//! Formatter composed of arbitrary components.
+
+use std::io;
+use std::marker::PhantomData;
+
+use super::{Backtrace, PanicFormat, PanicInfo, Prefix, Suffix};
+
+///Describes how to create writer of [Composite](struct.Composite.html)
+///
+///Implemented for any `io::Write` with `Default`, creating it via `Default::default()`
+pub trait MakeWriter {
+    ///Writer to create.
+    type Writer: io::Write;
+
+    ///Creates new writer.
+    fn make_writer() -> Self::Writer;
+}
+
+impl<W: io::Write + Default> MakeWriter for W {
+    type Writer = W;
+
+    #[inline]
+    fn make_writer() -> Self::Writer {
+        W::default()
+    }
+}
+
+///Creates buffered stderr, same as [Simple](struct.Simple.html)
+pub struct StderrWriter;
+
+impl MakeWriter for StderrWriter {
+    type Writer = io::BufWriter<io::Stderr>;
+
+    #[inline]
+    fn make_writer() -> Self::Writer {
+        io::BufWriter::new(io::stderr())
+    }
+}
+
+///Creates buffered stdout.
+pub struct StdoutWriter;
+
+impl MakeWriter for StdoutWriter {
+    type Writer = io::BufWriter<io::Stdout>;
+
+    #[inline]
+    fn make_writer() -> Self::Writer {
+        io::BufWriter::new(io::stdout())
+    }
+}
+
+///Formatter made of Prefix `P`, `PanicInfo` `I`, Suffix `S`, Backtrace `B` and writer created by `W`
+///
+///So that components can be mixed in type alias without writing any impl.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Composite, Debug, Empty, Simple, StderrWriter, Timestamp};
+///
+///type MyFormat = Composite<Timestamp, Simple, Empty, Debug, StderrWriter>;
+///
+///fn main() {
+///    set_panic_message!(MyFormat);
+///}
+///```
+pub struct Composite<P, I, S, B, W> {
+    _format: PhantomData<(P, I, S, B, W)>,
+}
+
+impl<P: Prefix, I: PanicInfo, S: Suffix, B: Backtrace, W: MakeWriter> PanicFormat for Composite<P, I, S, B, W> {
+    type Writer = W::Writer;
+    type Backtrace = B;
+    type Prefix = P;
+    type PanicInfo = I;
+    type Suffix = S;
+
+    #[inline]
+    fn writer() -> Self::Writer {
+        W::make_writer()
+    }
+}
@@ -0,0 +1,38 @@
+//! Integration with `defmt` crate.
+
+use std::io;
+use PanicHookInfo;
+
+use defmt;
+use location;
+use payload;
+use super::{Empty, PanicFormat};
+
+///Emits panic's location and message via `defmt::error!`
+///
+///Output goes through `defmt` global logger (e.g. RTT), so that firmware tooling can decode it.
+///
+///Note that final binary must provide `defmt` global logger to link.
+pub struct DefmtTarget;
+
+impl PanicFormat for DefmtTarget {
+    type Writer = io::Sink;
+    type Backtrace = Empty;
+    type Prefix = Empty;
+    type PanicInfo = Empty;
+    type Suffix = Empty;
+
+    fn writer() -> Self::Writer {
+        io::sink()
+    }
+
+    fn print(info: &PanicHookInfo) {
+        let (file, line, column) = match info.location() {
+            Some(location) => (location::strip(location.file()), location.line(), location.column()),
+            None => ("unknown", 0, 0),
+        };
+        let message = payload::to_string(info.payload());
+
+        defmt::error!("Panic: {=str}:{=u32}:{=u32} - {=str}", file, line, column, message.as_str());
+    }
+}
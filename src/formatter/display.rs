@@ -0,0 +1,32 @@
+//! Display of panic via formatter.
+
+use std::fmt;
+use std::marker::PhantomData;
+use PanicHookInfo;
+
+use super::PanicFormat;
+
+///Displays panic as written by components of `F`
+///
+///So that panic can be embedded into other messages, e.g. `format!("task failed: {}", DisplayPanic::<JustError>::new(info))`
+pub struct DisplayPanic<'a, F> {
+    info: &'a PanicHookInfo<'a>,
+    _format: PhantomData<F>,
+}
+
+impl<'a, F: PanicFormat> DisplayPanic<'a, F> {
+    ///Creates new instance.
+    pub fn new(info: &'a PanicHookInfo<'a>) -> Self {
+        DisplayPanic {
+            info,
+            _format: PhantomData,
+        }
+    }
+}
+
+impl<'a, F: PanicFormat> fmt::Display for DisplayPanic<'a, F> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        F::write_fmt_report(fmt, self.info)
+    }
+}
@@ -0,0 +1,154 @@
+//! Formatters carrying runtime state.
+
+use std::io::{self, Write};
+use PanicHookInfo;
+
+use super::PanicFormat;
+
+///Object-safe panic formatter, which can be installed as value.
+///
+///So that formatter can carry runtime state, e.g. file path or webhook URL read from configuration.
+///Installed via [hook::install_dyn](../hook/fn.install_dyn.html) or `set_panic_message!(dyn value)`
+///
+///Implemented for every [PanicFormat](trait.PanicFormat.html)
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::PanicHookInfo;
+///use lazy_panic::formatter::{DynPanicFormat, JustError, PanicFormat};
+///
+///use std::io;
+///use std::thread;
+///use std::sync::{Arc, Mutex};
+///
+///struct Tagged {
+///    tag: String,
+///    output: Arc<Mutex<Vec<u8>>>,
+///}
+///
+///impl DynPanicFormat for Tagged {
+///    fn write_dyn(&self, mut writer: &mut dyn io::Write, info: &PanicHookInfo) -> io::Result<()> {
+///        write!(writer, "[{}] ", self.tag)?;
+///        JustError::write_report(&mut writer, info)
+///    }
+///
+///    fn print_dyn(&self, info: &PanicHookInfo) {
+///        let _ = self.write_dyn(&mut *self.output.lock().unwrap(), info);
+///    }
+///}
+///
+///fn main() {
+///    let output = Arc::new(Mutex::new(Vec::new()));
+///    set_panic_message!(dyn Tagged { tag: "worker".to_owned(), output: output.clone() });
+///
+///    assert!(thread::spawn(|| panic!("lolka")).join().is_err());
+///    let output = output.lock().unwrap().clone();
+///    assert_eq!(String::from_utf8(output).unwrap(), "[worker] lolka\n");
+///}
+///```
+pub trait DynPanicFormat: Send + Sync {
+    ///Writes report into `writer`.
+    fn write_dyn(&self, writer: &mut dyn io::Write, info: &PanicHookInfo) -> io::Result<()>;
+
+    ///Prints panic.
+    ///
+    ///Writes report into stderr by default.
+    fn print_dyn(&self, info: &PanicHookInfo) {
+        let stderr = io::stderr();
+        let mut stderr = stderr.lock();
+        let _ = self.write_dyn(&mut stderr, info).and(stderr.flush());
+    }
+
+    ///Returns report in `String`, ignoring errors.
+    fn format_dyn(&self, info: &PanicHookInfo) -> String {
+        let mut report = Vec::new();
+        let _ = self.write_dyn(&mut report, info);
+        String::from_utf8_lossy(&report).into_owned()
+    }
+}
+
+impl<F: PanicFormat + Send + Sync> DynPanicFormat for F {
+    #[inline]
+    fn write_dyn(&self, mut writer: &mut dyn io::Write, info: &PanicHookInfo) -> io::Result<()> {
+        F::write_report(&mut writer, info)
+    }
+
+    #[inline]
+    fn print_dyn(&self, info: &PanicHookInfo) {
+        F::print(info)
+    }
+
+    #[inline]
+    fn format_dyn(&self, info: &PanicHookInfo) -> String {
+        F::format_to_string(info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io;
+    use std::process;
+    use std::thread;
+
+    use formatter::{JustError, PanicFormat};
+    use hook::{install_dyn, installed_format_name};
+    use test::PanicCapture;
+    use super::DynPanicFormat;
+
+    const DYN_CHILD: &str = "LAZY_PANIC_TEST_DYN_CHILD";
+
+    struct Tagged {
+        tag: String,
+    }
+
+    impl DynPanicFormat for Tagged {
+        fn write_dyn(&self, mut writer: &mut dyn io::Write, info: &::PanicHookInfo) -> io::Result<()> {
+            write!(writer, "[{}] ", self.tag)?;
+            JustError::write_report(&mut writer, info)
+        }
+    }
+
+    //Installed format is process-wide, so it runs in own process to not affect other tests.
+    #[test]
+    #[ignore]
+    fn dyn_child() {
+        if env::var_os(DYN_CHILD).is_none() {
+            return;
+        }
+
+        install_dyn(JustError);
+        assert!(installed_format_name().expect("To be installed").ends_with("JustError"));
+        {
+            let capture = PanicCapture::new();
+            assert!(thread::spawn(|| panic!("lolka")).join().is_err());
+            assert_eq!(capture.reports(), ["lolka\n"]);
+        }
+
+        set_panic_message!(dyn Tagged { tag: "worker".to_owned() });
+        assert!(installed_format_name().expect("To be installed").ends_with("Tagged"));
+        {
+            let capture = PanicCapture::new();
+            assert!(thread::spawn(|| panic!("lolka")).join().is_err());
+            assert_eq!(capture.reports(), ["[worker] lolka\n"]);
+        }
+
+        assert!(thread::spawn(|| panic!("lolka 2")).join().is_err());
+    }
+
+    #[test]
+    fn should_install_dyn_format() {
+        let output = process::Command::new(env::current_exe().expect("To get test executable"))
+                                      .args(["formatter::dynamic::tests::dyn_child", "--exact", "--ignored", "--nocapture", "--test-threads=1"])
+                                      .env(DYN_CHILD, "1")
+                                      .output()
+                                      .expect("To run child");
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{}{}", String::from_utf8_lossy(&output.stdout), stderr);
+        assert!(String::from_utf8_lossy(&output.stdout).contains("1 passed"));
+        assert_eq!(stderr, "[worker] lolka 2\n");
+    }
+}
@@ -0,0 +1,121 @@
+//! Escaping utilities for custom formatters.
+
+use std::borrow::Cow;
+use std::fmt::Write;
+
+#[inline]
+fn escape_with<F: Fn(char) -> bool, E: Fn(&mut String, char)>(text: &str, should_escape: F, escape: E) -> Cow<'_, str> {
+    let first = match text.find(&should_escape) {
+        Some(first) => first,
+        None => return Cow::Borrowed(text),
+    };
+
+    let mut result = String::with_capacity(text.len() + 8);
+    result.push_str(&text[..first]);
+    for ch in text[first..].chars() {
+        match should_escape(ch) {
+            true => escape(&mut result, ch),
+            false => result.push(ch),
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+#[inline]
+fn is_unsafe_control(ch: char) -> bool {
+    match ch {
+        '\n' | '\t' => false,
+        //Bidirectional overrides can be used to spoof text.
+        '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}' => true,
+        ch => ch.is_control(),
+    }
+}
+
+///Escapes control characters, except new line and tab, and bidirectional overrides.
+///
+///ASCII characters are escaped as `\x1b`, others as `\u{202e}`
+pub fn escape_control(text: &str) -> Cow<'_, str> {
+    escape_with(text, is_unsafe_control, |result, ch| {
+        let _ = match ch as u32 {
+            code if code < 0x80 => write!(result, "\\x{:02x}", code),
+            code => write!(result, "\\u{{{:x}}}", code),
+        };
+    })
+}
+
+///Escapes text to be used inside JSON string.
+///
+///Surrounding quotes are not added.
+pub fn escape_json(text: &str) -> Cow<'_, str> {
+    escape_with(text, |ch| ch == '"' || ch == '\\' || ch < ' ', |result, ch| match ch {
+        '"' => result.push_str("\\\""),
+        '\\' => result.push_str("\\\\"),
+        '\n' => result.push_str("\\n"),
+        '\r' => result.push_str("\\r"),
+        '\t' => result.push_str("\\t"),
+        '\u{8}' => result.push_str("\\b"),
+        '\u{c}' => result.push_str("\\f"),
+        ch => {
+            let _ = write!(result, "\\u{:04x}", ch as u32);
+        },
+    })
+}
+
+///Escapes text to be used as logfmt value.
+///
+///Value is quoted when it is empty or contains spaces, `=`, quotes or control characters.
+pub fn escape_logfmt(text: &str) -> Cow<'_, str> {
+    if !text.is_empty() && !text.contains(|ch: char| ch <= ' ' || ch == '=' || ch == '"' || ch == '\\' || ch.is_control()) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    result.push_str(&escape_json(text));
+    result.push('"');
+
+    Cow::Owned(result)
+}
+
+///Escapes text to be used as URL's query component.
+///
+///All characters, except unreserved (`A-Z a-z 0-9 - . _ ~`), are percent-encoded as UTF-8.
+pub fn escape_url(text: &str) -> Cow<'_, str> {
+    escape_with(text, |ch| !(ch.is_ascii_alphanumeric() || ch == '-' || ch == '.' || ch == '_' || ch == '~'), |result, ch| {
+        let mut buffer = [0; 4];
+        for byte in ch.encode_utf8(&mut buffer).bytes() {
+            let _ = write!(result, "%{:02X}", byte);
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_control, escape_json, escape_logfmt, escape_url};
+
+    #[test]
+    fn should_escape_control() {
+        assert_eq!(escape_control("plain\ttext\n"), "plain\ttext\n");
+        assert_eq!(escape_control("\x1b[31mred\r\u{202e}"), "\\x1b[31mred\\x0d\\u{202e}");
+    }
+
+    #[test]
+    fn should_escape_json() {
+        assert_eq!(escape_json("plain"), "plain");
+        assert_eq!(escape_json("\"quoted\"\\\n\u{1}"), "\\\"quoted\\\"\\\\\\n\\u0001");
+    }
+
+    #[test]
+    fn should_escape_logfmt() {
+        assert_eq!(escape_logfmt("plain"), "plain");
+        assert_eq!(escape_logfmt(""), "\"\"");
+        assert_eq!(escape_logfmt("key=value with \"quotes\""), "\"key=value with \\\"quotes\\\"\"");
+    }
+
+    #[test]
+    fn should_escape_url() {
+        assert_eq!(escape_url("plain-text_1.0~"), "plain-text_1.0~");
+        assert_eq!(escape_url("a b&c=d\nä"), "a%20b%26c%3Dd%0A%C3%A4");
+    }
+}
@@ -0,0 +1,165 @@
+//! Structured key/value fields.
+
+use std::io;
+use std::marker::PhantomData;
+use PanicHookInfo;
+
+use context;
+use location;
+use payload;
+use super::{Empty, Metadata, PanicFormat, PanicInfo, Suffix};
+use super::escape::{escape_json, escape_logfmt};
+
+///Describes key/value pairs of panic's metadata.
+///
+///Single definition is rendered consistently by text formatters, via [KeyValue](struct.KeyValue.html),
+///and structured ones, via [Json](struct.Json.html)
+///
+///Implemented by metadata components (e.g. [AppInfo](struct.AppInfo.html), [Pid](struct.Pid.html),
+///[Thread](struct.Thread.html) or [SystemInfo](struct.SystemInfo.html)) and tuples of `Fields`
+///
+///```rust
+///use lazy_panic::formatter::Fields;
+///
+///pub struct Region;
+///
+///impl Fields for Region {
+///    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+///        field("region", "eu-west");
+///    }
+///}
+///```
+pub trait Fields {
+    ///Calls `field` with each key/value pair in order.
+    fn fields<F: FnMut(&str, &str)>(field: &mut F);
+}
+
+impl Fields for Empty {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(_: &mut F) {
+    }
+}
+
+///Writes `T`'s [Fields](trait.Fields.html) as logfmt pairs.
+///
+///As [Suffix](trait.Suffix.html) it goes as ` build=abc123 region="eu west"`
+pub struct KeyValue<T> {
+    _fields: PhantomData<T>,
+}
+
+impl<T: Fields> Suffix for KeyValue<T> {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let mut result = Ok(());
+        T::fields(&mut |key, value| if result.is_ok() {
+            result = write!(writer, " {}={}", key, escape_logfmt(value));
+        });
+        result
+    }
+}
+
+///Writes panic as single line JSON object, followed by `\n`
+///
+///Object goes as `{"message":"lolka","location":"src/main.rs:5:5",...}` with members of `T`'s
+///[Fields](trait.Fields.html), defaulting to [Metadata](struct.Metadata.html), and `context` array,
+///when there is context.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{AppInfo, Json, Metadata};
+///
+///fn main() {
+///    app_info!();
+///    set_panic_message!(Json<(AppInfo, Metadata)>);
+///}
+///```
+pub struct Json<T = Metadata> {
+    _fields: PhantomData<T>,
+}
+
+impl<T: Fields> PanicInfo for Json<T> {
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        let mut message = Vec::new();
+        payload::write_unescaped(&mut message, info.payload())?;
+
+        let mut object = format!("{{\"message\":\"{}\"", escape_json(&String::from_utf8_lossy(&message)));
+        if let Some(location) = info.location() {
+            object.push_str(&format!(",\"location\":\"{}:{}:{}\"", escape_json(location::strip(location.file())), location.line(), location.column()));
+        }
+
+        T::fields(&mut |key, value| {
+            object.push_str(&format!(",\"{}\":\"{}\"", escape_json(key), escape_json(value)));
+        });
+
+        let entries = context::entries();
+        if !entries.is_empty() {
+            object.push_str(",\"context\":[");
+            for (idx, entry) in entries.iter().enumerate() {
+                let separator = if idx == 0 { "" } else { "," };
+                object.push_str(&format!("{}\"{}\"", separator, escape_json(entry)));
+            }
+            object.push(']');
+        }
+
+        object.push('}');
+        writer.write_all(object.as_bytes())
+    }
+}
+
+impl<T: Fields> Suffix for Json<T> {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        writeln!(writer)
+    }
+}
+
+impl<T: Fields> PanicFormat for Json<T> {
+    type Writer = io::BufWriter<io::Stderr>;
+    type Backtrace = Empty;
+    type Prefix = Empty;
+    type PanicInfo = Self;
+    type Suffix = Self;
+
+    fn writer() -> Self::Writer {
+        let stderr = io::stderr();
+        io::BufWriter::new(stderr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Composite, Deterministic, Empty, JustError, Pid, Thread};
+    use super::{Fields, Json, KeyValue};
+
+    struct Region;
+
+    impl Fields for Region {
+        fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+            field("region", "eu west");
+            field("zone", "b");
+        }
+    }
+
+    #[test]
+    fn should_render_fields() {
+        type Format = Composite<Empty, JustError, KeyValue<(Region, Empty)>, Empty, io::Sink>;
+
+        assert_eq!(::test::format_panic::<Format, _>("lolka"), "lolka region=\"eu west\" zone=b");
+
+        let output = ::test::format_panic::<Json<Region>, _>("lol\"ka\n\r");
+        assert!(output.starts_with("{\"message\":\"lol\\\"ka\\n\\r\",\"location\":\"src/hook/test.rs:"), "{}", output);
+        assert!(output.ends_with(",\"region\":\"eu west\",\"zone\":\"b\"}\n"), "{}", output);
+    }
+
+    #[test]
+    fn should_render_component_fields() {
+        type Format = Deterministic<Composite<Empty, JustError, KeyValue<(Pid, Thread)>, Empty, io::Sink>>;
+
+        let output = ::test::format_panic::<Format, _>("lolka");
+        assert!(output.starts_with("lolka pid=<pid> thread=formatter::fields::tests::"), "{}", output);
+        assert!(output.ends_with(" tid=<tid>"), "{}", output);
+    }
+}
@@ -0,0 +1,74 @@
+//! Components made of plain functions.
+
+use std::io;
+use std::marker::PhantomData;
+
+use super::{Prefix, Suffix};
+
+///Describes function, called by [FnPrefix](struct.FnPrefix.html) and [FnSuffix](struct.FnSuffix.html)
+///
+///Can be defined via [define_fn!](../macro.define_fn.html)
+pub trait WriteFn {
+    ///Function writing component.
+    const WRITE: fn(&mut dyn io::Write) -> io::Result<()>;
+}
+
+///Writes via `T::WRITE` as [Prefix](trait.Prefix.html)
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Composite, FnPrefix, Debug, Simple, StderrWriter};
+///
+///define_fn!(Pid = |writer| write!(writer, "[{}] ", std::process::id()));
+///
+///type MyFormat = Composite<FnPrefix<Pid>, Simple, Simple, Debug, StderrWriter>;
+///
+///fn main() {
+///    set_panic_message!(MyFormat);
+///}
+///```
+pub struct FnPrefix<T> {
+    _fn: PhantomData<T>,
+}
+
+impl<T: WriteFn> Prefix for FnPrefix<T> {
+    #[inline]
+    fn write_in<W: io::Write>(mut writer: &mut W) -> io::Result<()> {
+        (T::WRITE)(&mut writer)
+    }
+}
+
+///Writes via `T::WRITE` as [Suffix](trait.Suffix.html)
+pub struct FnSuffix<T> {
+    _fn: PhantomData<T>,
+}
+
+impl<T: WriteFn> Suffix for FnSuffix<T> {
+    #[inline]
+    fn write_in<W: io::Write>(mut writer: &mut W) -> io::Result<()> {
+        (T::WRITE)(&mut writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Composite, Empty, FnPrefix, FnSuffix, JustError};
+
+    fn arrow(writer: &mut dyn io::Write) -> io::Result<()> {
+        writer.write_all(b"-> ")
+    }
+
+    define_fn!(Arrow = arrow);
+    define_fn!(Code = |writer| write!(writer, " (code {})", 42));
+
+    #[test]
+    fn should_write_via_fn() {
+        type Format = Composite<FnPrefix<Arrow>, JustError, FnSuffix<Code>, Empty, io::Sink>;
+
+        assert_eq!(::test::format_panic::<Format, _>("lolka"), "-> lolka (code 42)");
+    }
+}
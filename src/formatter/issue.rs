@@ -0,0 +1,97 @@
+//! Prefilled issue URL.
+
+use std::io;
+use std::marker::PhantomData;
+use PanicHookInfo;
+
+use location;
+use metadata;
+use payload;
+use super::{Debug, PanicInfo};
+use super::escape::escape_url;
+
+const MAX_TITLE_LEN: usize = 100;
+//Keeps URL within limits of browsers and GitHub.
+const MAX_BACKTRACE_LINES: usize = 30;
+
+///Describes GitHub repository for [IssueUrl](struct.IssueUrl.html)
+pub trait Repository {
+    ///URL of repository, e.g. `https://github.com/DoumanAsh/lazy-panic.rs`
+    const URL: &'static str;
+}
+
+///Writes URL of new GitHub issue, prefilled with panic's message, location, version and backtrace.
+///
+///As [PanicInfo](trait.PanicInfo.html) it goes as `\nPlease report this at: https://github.com/org/repo/issues/new?title=...&body=...`
+///
+///Version is taken from [app_info](../metadata/fn.app_info.html), while backtrace is of
+///[Debug](struct.Debug.html), trimmed to first frames.
+///
+///```rust
+///use lazy_panic::formatter::{IssueUrl, Repository};
+///
+///pub struct Repo;
+///
+///impl Repository for Repo {
+///    const URL: &'static str = "https://github.com/DoumanAsh/lazy-panic.rs";
+///}
+///
+///pub type ReportIssue = IssueUrl<Repo>;
+///```
+pub struct IssueUrl<R> {
+    _repository: PhantomData<R>,
+}
+
+impl<R: Repository> PanicInfo for IssueUrl<R> {
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        let message = payload::to_string(info.payload());
+        let title = message.lines().next().unwrap_or("").chars().take(MAX_TITLE_LEN).collect::<String>();
+
+        let mut body = format!("**Message:**\n```\n{}\n```\n", message);
+        if let Some(location) = info.location() {
+            body.push_str(&format!("**Location:** `{}:{}:{}`\n", location::strip(location.file()), location.line(), location.column()));
+        }
+        match metadata::app_info() {
+            Some((name, version)) => body.push_str(&format!("**Version:** {} {}\n", name, version)),
+            None => body.push_str("**Version:** unknown\n"),
+        }
+
+        let mut backtrace = Vec::new();
+        let _ = Debug::write_untranslated(&mut backtrace);
+        if !backtrace.is_empty() {
+            let backtrace = String::from_utf8_lossy(&backtrace);
+            body.push_str("\n**Backtrace:**\n```\n");
+            for line in backtrace.lines().take(MAX_BACKTRACE_LINES) {
+                body.push_str(line);
+                body.push('\n');
+            }
+            body.push_str("```\n");
+        }
+
+        write!(writer, "\nPlease report this at: {}/issues/new?title={}&body={}", R::URL.trim_end_matches('/'), escape_url(&title), escape_url(&body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Composite, Empty};
+    use test::capture_report;
+    use super::{IssueUrl, Repository};
+
+    struct Repo;
+
+    impl Repository for Repo {
+        const URL: &'static str = "https://github.com/DoumanAsh/lazy-panic.rs/";
+    }
+
+    type IssueFormat = Composite<Empty, IssueUrl<Repo>, Empty, Empty, io::Sink>;
+
+    #[test]
+    fn should_write_issue_url() {
+        let report = capture_report::<IssueFormat, _, _>(|| panic!("lolka & co\nsecond line")).expect("To capture report");
+        assert!(report.starts_with("\nPlease report this at: https://github.com/DoumanAsh/lazy-panic.rs/issues/new?title=lolka%20%26%20co&body=%2A%2AMessage%3A%2A%2A%0A%60%60%60%0Alolka%20%26%20co%0Asecond%20line%0A"), "{}", report);
+        assert!(report.contains("%2A%2ALocation%3A%2A%2A%20%60src%2Fformatter%2Fissue.rs%3A"), "{}", report);
+    }
+}
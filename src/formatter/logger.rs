@@ -0,0 +1,104 @@
+//! Integration with `log` crate.
+
+use std::io;
+
+use log;
+use super::{Debug, Empty, PanicFormat, Simple};
+
+///Buffers panic's report until it is emitted via `log`.
+pub struct LogWriter {
+    buffer: Vec<u8>,
+}
+
+impl io::Write for LogWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+///Emits panic via `log::error!` with target `panic`.
+///
+///So that panic goes through application's logger configuration, including filtering,
+///instead of raw stderr.
+///
+///Prefix is omitted as logger has its own.
+///For `PanicInfo` and Suffix it is the same as [Simple](struct.Simple.html),
+///with backtrace of [Debug](struct.Debug.html) going at the end of the message.
+///
+///Note that panic is not printed if logger is not initialized.
+pub struct LogTarget;
+
+impl PanicFormat for LogTarget {
+    type Writer = LogWriter;
+    type Backtrace = Debug;
+    type Prefix = Empty;
+    type PanicInfo = Simple;
+    type Suffix = Simple;
+
+    //Puts message first, as backtrace goes right after prefix otherwise.
+    const TWO_STAGE: bool = true;
+
+    fn writer() -> Self::Writer {
+        LogWriter {
+            buffer: Vec::new(),
+        }
+    }
+
+    fn finalize(writer: Self::Writer) -> io::Result<()> {
+        let message = String::from_utf8_lossy(&writer.buffer);
+        log::error!(target: "panic", "{}", message.trim_end());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::thread;
+
+    use log;
+    use hook::{install, set_thread_format};
+    use formatter::Simple;
+    use super::LogTarget;
+
+    static RECORDS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Logger;
+
+    impl log::Log for Logger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            RECORDS.lock().unwrap().push(format!("{}: {}", record.target(), record.args()));
+        }
+
+        fn flush(&self) {
+        }
+    }
+
+    #[test]
+    fn should_emit_panic_via_log() {
+        static LOGGER: Logger = Logger;
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Error);
+        install::<Simple>();
+
+        let worker = thread::spawn(|| {
+            set_thread_format::<LogTarget>();
+            panic!("lolka");
+        });
+        assert!(worker.join().is_err());
+
+        let records = RECORDS.lock().unwrap();
+        assert!(records.iter().any(|record| record.starts_with("panic: src/formatter/logger.rs") && record.contains(" - lolka")), "{:?}", records);
+    }
+}
@@ -0,0 +1,160 @@
+//! Strategies for errors of panic's writer.
+
+use std::io::{self, Write};
+use std::process;
+use PanicHookInfo;
+
+use location;
+
+///Describes what to do when writer of [PanicFormat](trait.PanicFormat.html) fails.
+///
+///```rust
+///use lazy_panic::PanicHookInfo;
+///use lazy_panic::formatter::{self, PanicFormat, OnWriteError, FallbackStderr};
+///
+///use std::io;
+///
+///struct ToFile;
+///
+///impl PanicFormat for ToFile {
+///    type Writer = std::fs::File;
+///    type Backtrace = formatter::Simple;
+///    type Prefix = formatter::Simple;
+///    type PanicInfo = formatter::Simple;
+///    type Suffix = formatter::Simple;
+///
+///    fn writer() -> Self::Writer {
+///        std::fs::File::create("/dev/full").expect("To open file")
+///    }
+///
+///    fn on_write_error(info: &PanicHookInfo, error: io::Error) {
+///        FallbackStderr::on_write_error(info, error)
+///    }
+///}
+///```
+pub trait OnWriteError {
+    fn on_write_error(info: &PanicHookInfo, error: io::Error);
+}
+
+///Ignores error.
+pub struct Ignore;
+
+impl OnWriteError for Ignore {
+    #[inline]
+    fn on_write_error(_: &PanicHookInfo, _: io::Error) {
+    }
+}
+
+///Writes panic's location and payload to stderr along with writer's error.
+pub struct FallbackStderr;
+
+impl OnWriteError for FallbackStderr {
+    fn on_write_error(info: &PanicHookInfo, error: io::Error) {
+        let stderr = io::stderr();
+        let mut stderr = stderr.lock();
+
+        let _ = match info.location() {
+            Some(location) => write!(stderr, "{}:{}:{} - ", location::strip(location.file()), location.line(), location.column()),
+            None  => write!(stderr, "unknown:0:0 - ")
+        };
+        let _ = write_payload_default!(&mut stderr, info.payload());
+        let _ = writeln!(stderr, "\nFailed to write panic: {}", error);
+    }
+}
+
+///Aborts process, so that failure to report panic is not missed.
+pub struct AbortOnError;
+
+impl OnWriteError for AbortOnError {
+    #[inline]
+    fn on_write_error(_: &PanicHookInfo, _: io::Error) {
+        process::abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io;
+    use std::marker::PhantomData;
+    use std::process;
+
+    use formatter::{JustError, PanicFormat, Simple};
+    use hook::install;
+    use super::{OnWriteError, Ignore, FallbackStderr, AbortOnError};
+
+    const STRATEGY_CHILD: &str = "LAZY_PANIC_TEST_STRATEGY_CHILD";
+
+    struct FailingWriter;
+
+    impl io::Write for FailingWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("lolka writer"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Failing<S> {
+        _strategy: PhantomData<S>,
+    }
+
+    impl<S: OnWriteError> PanicFormat for Failing<S> {
+        type Writer = FailingWriter;
+        type Backtrace = Simple;
+        type Prefix = Simple;
+        type PanicInfo = JustError;
+        type Suffix = Simple;
+
+        fn writer() -> Self::Writer {
+            FailingWriter
+        }
+
+        fn on_write_error(info: &::PanicHookInfo, error: io::Error) {
+            S::on_write_error(info, error)
+        }
+    }
+
+    //Strategies write to stderr or abort, so each one runs in own process.
+    #[test]
+    #[ignore]
+    fn strategy_child() {
+        match env::var(STRATEGY_CHILD).as_ref().map(String::as_str) {
+            Ok("ignore") => install::<Failing<Ignore>>(),
+            Ok("fallback") => install::<Failing<FallbackStderr>>(),
+            Ok("abort") => install::<Failing<AbortOnError>>(),
+            _ => return,
+        }
+
+        panic!("lolka");
+    }
+
+    fn run_child(strategy: &str) -> (process::ExitStatus, String) {
+        let output = process::Command::new(env::current_exe().expect("To get test executable"))
+                                      .args(["formatter::on_error::tests::strategy_child", "--exact", "--ignored", "--nocapture", "--test-threads=1"])
+                                      .env(STRATEGY_CHILD, strategy)
+                                      .output()
+                                      .expect("To run child");
+
+        (output.status, String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+
+    #[test]
+    fn should_handle_write_error() {
+        let (status, stderr) = run_child("ignore");
+        assert_eq!(status.code(), Some(101), "{}", stderr);
+        assert!(!stderr.contains("lolka"), "{}", stderr);
+
+        let (status, stderr) = run_child("fallback");
+        assert_eq!(status.code(), Some(101), "{}", stderr);
+        assert!(stderr.starts_with("src/formatter/on_error.rs:"), "{}", stderr);
+        assert!(stderr.contains(" - lolka\nFailed to write panic: lolka writer\n"), "{}", stderr);
+
+        let (status, stderr) = run_child("abort");
+        assert!(!status.success(), "{}", stderr);
+        assert_ne!(status.code(), Some(101), "{}", stderr);
+        assert!(!stderr.contains("lolka"), "{}", stderr);
+    }
+}
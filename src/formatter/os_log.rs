@@ -0,0 +1,208 @@
+//! Integration with Apple's unified logging.
+
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::raw::{c_char, c_void};
+use std::sync::Mutex;
+
+use lock;
+use metadata;
+use super::{Debug, Empty, PanicFormat, Simple};
+
+#[allow(non_camel_case_types)]
+type os_log_t = *mut c_void;
+
+//OS_LOG_TYPE_FAULT
+const FAULT: u8 = 0x11;
+//Unified logging truncates longer messages.
+const MAX_RECORD: usize = 1024;
+//Size of pointer to string argument.
+const POINTER_SIZE: usize = mem::size_of::<*const c_char>();
+
+//Handle of `os_log_create`, created once per subsystem and never released.
+static LOG: Mutex<Option<(&'static str, usize)>> = Mutex::new(None);
+
+extern "C" {
+    static __dso_handle: u8;
+
+    fn os_log_create(subsystem: *const c_char, category: *const c_char) -> os_log_t;
+    fn _os_log_impl(dso: *const c_void, log: os_log_t, kind: u8, format: *const c_char, buffer: *const u8, size: u32);
+}
+
+//Format string must reside in binary's image, as only offset to it is stored.
+#[link_section = "__TEXT,__oslogstring,cstring_literals"]
+static FORMAT: [u8; 11] = *b"%{public}s\0";
+
+fn fault(log: os_log_t, message: &str) {
+    let message = match CString::new(message) {
+        Ok(message) => message,
+        Err(error) => {
+            let mut message = error.into_vec();
+            message.retain(|byte| *byte != 0);
+            CString::new(message).unwrap_or_default()
+        },
+    };
+
+    //Encoded arguments, as built by `os_log` macro: summary, count and then public string,
+    //which is passed as pointer of target's size.
+    let mut buffer = [0u8; 4 + POINTER_SIZE];
+    buffer[0] = 0x02;
+    buffer[1] = 1;
+    buffer[2] = 0x22;
+    buffer[3] = POINTER_SIZE as u8;
+    buffer[4..].copy_from_slice(&(message.as_ptr() as usize).to_ne_bytes());
+
+    unsafe {
+        _os_log_impl(&__dso_handle as *const u8 as *const c_void, log, FAULT, FORMAT.as_ptr() as *const c_char, buffer.as_ptr(), buffer.len() as u32);
+    }
+}
+
+fn log_handle(subsystem: &'static str) -> os_log_t {
+    let mut log = lock(&LOG);
+    match *log {
+        Some((cached, handle)) if cached == subsystem => handle as os_log_t,
+        _ => {
+            let name = CString::new(subsystem).unwrap_or_default();
+            let handle = unsafe { os_log_create(name.as_ptr(), b"panic\0".as_ptr() as *const c_char) };
+            *log = Some((subsystem, handle as usize));
+            handle
+        },
+    }
+}
+
+//Splits text by lines into records of up to `MAX_RECORD` bytes, cutting longer lines.
+fn records(text: &str) -> Vec<String> {
+    let mut records = Vec::new();
+    let mut record = String::new();
+
+    for line in text.lines() {
+        let mut line = line;
+        loop {
+            let separator = if record.is_empty() { 0 } else { 1 };
+            let available = MAX_RECORD - record.len() - separator;
+            if line.len() <= available {
+                if separator != 0 {
+                    record.push('\n');
+                }
+                record.push_str(line);
+                break;
+            }
+
+            if !record.is_empty() {
+                records.push(record.split_off(0));
+                continue;
+            }
+
+            //Characters are not split between records.
+            let mut end = MAX_RECORD;
+            while !line.is_char_boundary(end) {
+                end -= 1;
+            }
+            records.push(line[..end].to_owned());
+            line = &line[end..];
+        }
+    }
+
+    if !record.is_empty() {
+        records.push(record);
+    }
+    records
+}
+
+///Emits buffered text via `os_log` at fault level on flush.
+///
+///Subsystem is application's name from [app_info](../metadata/fn.app_info.html) and category is `panic`.
+///Text is split by lines into records of up to 1024 bytes, with longer lines cut into several records.
+pub struct OsLogWriter {
+    buffer: Vec<u8>,
+}
+
+impl OsLogWriter {
+    ///Creates new instance.
+    pub fn new() -> Self {
+        OsLogWriter {
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl Default for OsLogWriter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl io::Write for OsLogWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let text = String::from_utf8_lossy(&self.buffer).into_owned();
+        self.buffer.clear();
+
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        let subsystem = match metadata::app_info() {
+            Some((name, _)) => name,
+            None => "lazy-panic",
+        };
+        let log = log_handle(subsystem);
+
+        for record in records(text) {
+            fault(log, &record);
+        }
+
+        Ok(())
+    }
+}
+
+///Emits panic via `os_log` at fault level.
+///
+///Stderr of agents managed by `launchd` and of GUI applications doesn't reach Console.app,
+///while unified log does.
+///
+///Message goes as separate record ahead of backtrace of [Debug](struct.Debug.html).
+///Prefix is omitted as unified log has its own.
+pub struct OsLog;
+
+impl PanicFormat for OsLog {
+    type Writer = OsLogWriter;
+    type Backtrace = Debug;
+    type Prefix = Empty;
+    type PanicInfo = Simple;
+    type Suffix = Simple;
+
+    const TWO_STAGE: bool = true;
+
+    fn writer() -> Self::Writer {
+        OsLogWriter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{records, MAX_RECORD};
+
+    #[test]
+    fn should_split_records() {
+        assert_eq!(records("lolka\nkek"), ["lolka\nkek"]);
+
+        let line = "a".repeat(MAX_RECORD - 4);
+        let text = format!("{}\nlolka", line);
+        assert_eq!(records(&text), [line.clone(), "lolka".to_owned()]);
+
+        let long = "ё".repeat(MAX_RECORD);
+        let split = records(&format!("lolka\n{}", long));
+        assert_eq!(split[0], "lolka");
+        assert_eq!(split[1..].concat(), long);
+        assert!(split.iter().all(|record| record.len() <= MAX_RECORD));
+    }
+}
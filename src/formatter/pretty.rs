@@ -0,0 +1,192 @@
+//! Human friendly panic report.
+
+use std::io;
+use PanicHookInfo;
+
+use context;
+use locale::{self, Key};
+use location;
+use metadata;
+use super::escape::escape_logfmt;
+use super::{is_color_enabled, Backtrace, PanicFormat, PanicInfo, Prefix, Suffix};
+
+struct Palette {
+    reset: &'static str,
+    bold_red: &'static str,
+    red: &'static str,
+    purple: &'static str,
+    cyan: &'static str,
+    dim: &'static str,
+}
+
+const COLORED: Palette = Palette {
+    reset: "\x1b[0m",
+    bold_red: "\x1b[1;31m",
+    red: "\x1b[31m",
+    purple: "\x1b[35m",
+    cyan: "\x1b[36m",
+    dim: "\x1b[2m",
+};
+
+const PLAIN: Palette = Palette {
+    reset: "",
+    bold_red: "",
+    red: "",
+    purple: "",
+    cyan: "",
+    dim: "",
+};
+
+#[inline]
+fn palette() -> &'static Palette {
+    match is_color_enabled() {
+        true => &COLORED,
+        false => &PLAIN,
+    }
+}
+
+fn write_header<W: io::Write>(writer: &mut W, colors: &Palette, title: Key) -> io::Result<()> {
+    write!(writer, "\n{}━━━━━━━━━━━━━━━━━ {} ━━━━━━━━━━━━━━━━━{}", colors.dim, locale::text(title), colors.reset)
+}
+
+//Backtrace is printed when requested same way as by default hook.
+#[cfg(feature = "backtrace-on")]
+fn is_backtrace_requested() -> bool {
+    use std::env;
+
+    match env::var_os("RUST_BACKTRACE") {
+        Some(value) => value != "0",
+        None => false,
+    }
+}
+
+///Colored report with sections, similar to `color-eyre`
+///
+///Prefix is header `The application panicked (crashed).`
+///
+///`PanicInfo` is `Message` and `Location` lines.
+///
+///Suffix is `METADATA` and `CONTEXT` sections, if there is anything to write.
+///Metadata values are escaped as in [KeyValue](struct.KeyValue.html)
+///
+///Backtrace is `BACKTRACE` section, when requested by `RUST_BACKTRACE` and `backtrace-on` is enabled.
+///Otherwise notice on how to get it. Followed by suggestion footer.
+///
+///Header is flushed first, while backtrace goes last.
+///
+///Colors are written only into terminal, unless disabled via `NO_COLOR` or forced via `CLICOLOR_FORCE`,
+///see [is_color_enabled](fn.is_color_enabled.html)
+pub struct Pretty;
+
+impl Prefix for Pretty {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let colors = palette();
+        writeln!(writer, "{}{}{}", colors.bold_red, locale::text(Key::Crashed), colors.reset)
+    }
+}
+
+impl PanicInfo for Pretty {
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        let colors = palette();
+        let message = format!("{}:", locale::text(Key::Message));
+        let location_label = format!("{}:", locale::text(Key::Location));
+        //Values are aligned, as in `Message:  ` and `Location: `
+        let width = message.chars().count().max(location_label.chars().count()) + 1;
+
+        write!(writer, "{:width$}{}", message, colors.red, width = width)?;
+        write_payload_default!(writer, info.payload())?;
+        writeln!(writer, "{}", colors.reset)?;
+
+        match info.location() {
+            Some(location) => writeln!(writer, "{:width$}{}{}{}:{}{}:{}{}", location_label, colors.purple, location::strip(location.file()), colors.reset, colors.purple, location.line(), location.column(), colors.reset, width = width),
+            None => writeln!(writer, "{:width$}{}unknown{}", location_label, colors.purple, colors.reset, width = width),
+        }
+    }
+}
+
+impl Suffix for Pretty {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let colors = palette();
+        let entries = metadata::entries();
+        if !entries.is_empty() {
+            write_header(writer, colors, Key::Metadata)?;
+            for (key, value) in entries {
+                write!(writer, "\n{}{}{}: {}", colors.cyan, key, colors.reset, escape_logfmt(&value))?;
+            }
+            writeln!(writer)?;
+        }
+
+        let entries = context::entries();
+        if !entries.is_empty() {
+            write_header(writer, colors, Key::Context)?;
+            for (idx, entry) in entries.iter().enumerate() {
+                write!(writer, "\n{:4}: {}", idx, entry)?;
+            }
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Backtrace for Pretty {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let colors = palette();
+        #[cfg(feature = "backtrace-on")]
+        {
+            if is_backtrace_requested() {
+                write_header(writer, colors, Key::Backtrace)?;
+                super::write_backtrace(writer)?;
+                writeln!(writer)?;
+            } else {
+                writeln!(writer, "\n{}{}{}", colors.dim, locale::text(Key::BacktraceNotRequested), colors.reset)?;
+            }
+        }
+        #[cfg(not(feature = "backtrace-on"))]
+        {
+            writeln!(writer, "\n{}{}{}", colors.dim, locale::text(Key::BacktraceDisabled), colors.reset)?;
+        }
+
+        writeln!(writer, "{}{}{}", colors.dim, locale::text(Key::ReportIssue), colors.reset)
+    }
+}
+
+impl PanicFormat for Pretty {
+    type Writer = io::BufWriter<io::Stderr>;
+    type Backtrace = Self;
+    type Prefix = Self;
+    type PanicInfo = Self;
+    type Suffix = Self;
+
+    const TWO_STAGE: bool = true;
+
+    fn writer() -> Self::Writer {
+        io::BufWriter::new(io::stderr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Composite, Empty};
+    use metadata;
+    use test::capture_report;
+    use super::Pretty;
+
+    type PrettyFormat = Composite<Empty, Pretty, Pretty, Empty, io::Sink>;
+
+    #[test]
+    fn should_write_pretty_report() {
+        metadata::insert("pretty_forged", "lolka\n\x1b[31mLocation: kek");
+
+        let line = line!() + 1;
+        let report = capture_report::<PrettyFormat, _, _>(|| panic!("lolka")).expect("To capture report");
+        metadata::remove("pretty_forged");
+
+        assert!(report.contains(&format!("src/formatter/pretty.rs:{}:", line)), "{}", report);
+        assert!(report.contains("pretty_forged"), "{}", report);
+        assert!(report.contains(": \"lolka\\n\\u001b[31mLocation: kek\""), "{}", report);
+    }
+}
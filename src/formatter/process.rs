@@ -0,0 +1,311 @@
+//! Process related components.
+
+use std::env;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::fs;
+use std::io;
+use std::marker::PhantomData;
+use std::process;
+
+use hook;
+use super::{is_deterministic, Fields, Prefix, Suffix};
+
+///Writes id of the current process.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[pid 1234] `
+///
+///As [Fields](trait.Fields.html) it goes as `pid`
+pub struct Pid;
+
+impl Prefix for Pid {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match is_deterministic() {
+            true => writer.write_all(b"[pid <pid>] "),
+            false => write!(writer, "[pid {}] ", process::id()),
+        }
+    }
+}
+
+impl Fields for Pid {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        match is_deterministic() {
+            true => field("pid", "<pid>"),
+            false => field("pid", &process::id().to_string()),
+        }
+    }
+}
+
+///Writes number of panics handled by installed hook, including current one.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[panic #3] `
+///
+///As [Fields](trait.Fields.html) it goes as `panic_count`
+///
+///Useful when process survives panics via `catch_unwind`
+pub struct Counter;
+
+impl Prefix for Counter {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match is_deterministic() {
+            true => writer.write_all(b"[panic #<n>] "),
+            false => write!(writer, "[panic #{}] ", hook::panic_count()),
+        }
+    }
+}
+
+impl Fields for Counter {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        match is_deterministic() {
+            true => field("panic_count", "<n>"),
+            false => field("panic_count", &hook::panic_count().to_string()),
+        }
+    }
+}
+
+///Describes environment variables to be written by [Env](struct.Env.html)
+pub trait EnvList {
+    ///Names of environment variables.
+    const VARS: &'static [&'static str];
+}
+
+///Writes allowed list of environment variables.
+///
+///As [Suffix](trait.Suffix.html) it goes as `\nEnvironment: RUST_LOG="info" APP_MODE=<unset>`
+///
+///```rust
+///use lazy_panic::formatter::{Env, EnvList};
+///
+///pub struct AppEnv;
+///
+///impl EnvList for AppEnv {
+///    const VARS: &'static [&'static str] = &["RUST_LOG", "APP_MODE"];
+///}
+///
+///pub type AppEnvSuffix = Env<AppEnv>;
+///```
+pub struct Env<L> {
+    _list: PhantomData<L>,
+}
+
+impl<L: EnvList> Suffix for Env<L> {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        writer.write_all(b"\nEnvironment:")?;
+        for name in L::VARS {
+            match env::var_os(name) {
+                Some(value) => write!(writer, " {}={:?}", name, value.to_string_lossy()),
+                None => write!(writer, " {}=<unset>", name),
+            }?;
+        }
+
+        Ok(())
+    }
+}
+
+///Describes command line flags whose values are redacted by [Args](struct.Args.html)
+pub trait ArgsRedaction {
+    ///Flags whose values are hidden.
+    ///
+    ///Both `--flag value` and `--flag=value` forms are redacted.
+    const FLAGS: &'static [&'static str];
+}
+
+///Redacts nothing.
+pub struct NoRedaction;
+
+impl ArgsRedaction for NoRedaction {
+    const FLAGS: &'static [&'static str] = &[];
+}
+
+///Writes command line arguments of the process.
+///
+///As [Suffix](trait.Suffix.html) it goes as `\nArguments: "app" "--password" "***"`
+///
+///Values of flags listed by `R` are written as `***`
+pub struct Args<R = NoRedaction> {
+    _redaction: PhantomData<R>,
+}
+
+impl<R: ArgsRedaction> Args<R> {
+    fn write_args<W: io::Write, I: Iterator<Item=String>>(writer: &mut W, args: I) -> io::Result<()> {
+        const REDACTED: &str = "***";
+
+        writer.write_all(b"\nArguments:")?;
+
+        let mut redact_next = false;
+        for arg in args {
+            if redact_next {
+                redact_next = false;
+                write!(writer, " {:?}", REDACTED)?;
+                continue;
+            }
+
+            let flag = R::FLAGS.iter().find(|flag| arg.starts_with(*flag) && arg[flag.len()..].starts_with('='));
+            match flag {
+                Some(flag) => write!(writer, " \"{}={}\"", flag, REDACTED)?,
+                None => {
+                    redact_next = R::FLAGS.contains(&arg.as_str());
+                    write!(writer, " {:?}", arg)?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: ArgsRedaction> Suffix for Args<R> {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        Self::write_args(writer, env::args_os().map(|arg| arg.to_string_lossy().into_owned()))
+    }
+}
+
+///Writes memory usage of the process.
+///
+///As [Suffix](trait.Suffix.html) it goes as `\nMemory: rss=1024 KiB virtual=4096 KiB`
+///
+///As [Fields](trait.Fields.html) it goes as `rss_kib` and `virtual_kib`
+///
+///Supported on Linux and Windows. On other platforms values are written as `unknown`.
+pub struct Memory;
+
+impl Suffix for Memory {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        if is_deterministic() {
+            return writer.write_all(b"\nMemory: rss=<rss> virtual=<virtual>");
+        }
+
+        writer.write_all(b"\nMemory:")?;
+
+        let usage = memory_usage();
+        for &(name, value) in &[("rss", usage.map(|usage| usage.0)), ("virtual", usage.map(|usage| usage.1))] {
+            match value {
+                Some(value) => write!(writer, " {}={} KiB", name, value),
+                None => write!(writer, " {}=unknown", name),
+            }?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Fields for Memory {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if is_deterministic() {
+            field("rss_kib", "<rss>");
+            return field("virtual_kib", "<virtual>");
+        }
+
+        let usage = memory_usage();
+        for &(name, value) in &[("rss_kib", usage.map(|usage| usage.0)), ("virtual_kib", usage.map(|usage| usage.1))] {
+            match value {
+                Some(value) => field(name, &value.to_string()),
+                None => field(name, "unknown"),
+            }
+        }
+    }
+}
+
+//Returns resident and virtual memory in KiB
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn memory_usage() -> Option<(u64, u64)> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let field = |name: &str| status.lines()
+                                   .find(|line| line.starts_with(name))
+                                   .and_then(|line| line[name.len()..].trim().trim_end_matches("kB").trim().parse().ok());
+
+    Some((field("VmRSS:")?, field("VmSize:")?))
+}
+
+#[cfg(windows)]
+fn memory_usage() -> Option<(u64, u64)> {
+    use std::mem;
+    use std::os::raw::c_void;
+
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        working_set_size: usize,
+        quota_peak_paged_pool_usage: usize,
+        quota_paged_pool_usage: usize,
+        quota_peak_non_paged_pool_usage: usize,
+        quota_non_paged_pool_usage: usize,
+        pagefile_usage: usize,
+        peak_pagefile_usage: usize,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentProcess() -> *mut c_void;
+        fn K32GetProcessMemoryInfo(process: *mut c_void, counters: *mut ProcessMemoryCounters, cb: u32) -> i32;
+    }
+
+    unsafe {
+        let mut counters: ProcessMemoryCounters = mem::zeroed();
+        let size = mem::size_of::<ProcessMemoryCounters>() as u32;
+        counters.cb = size;
+
+        match K32GetProcessMemoryInfo(GetCurrentProcess(), &mut counters, size) {
+            0 => None,
+            _ => Some((counters.working_set_size as u64 / 1024, counters.pagefile_usage as u64 / 1024)),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+fn memory_usage() -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Suffix, Env, EnvList, Args, ArgsRedaction};
+
+    struct TestEnv;
+
+    impl EnvList for TestEnv {
+        const VARS: &'static [&'static str] = &["CARGO_PKG_NAME", "LAZY_PANIC_UNSET_VARIABLE"];
+    }
+
+    #[test]
+    fn should_write_env() {
+        let mut buffer = Vec::new();
+        Env::<TestEnv>::write_in(&mut buffer).expect("To write");
+        assert_eq!(buffer, &b"\nEnvironment: CARGO_PKG_NAME=\"lazy-panic\" LAZY_PANIC_UNSET_VARIABLE=<unset>"[..]);
+    }
+
+    struct TestRedaction;
+
+    impl ArgsRedaction for TestRedaction {
+        const FLAGS: &'static [&'static str] = &["--password", "--token"];
+    }
+
+    #[test]
+    fn should_redact_args() {
+        let args = ["app", "--password", "secret", "--token=secret", "--user", "douman"];
+
+        let mut buffer = Vec::new();
+        Args::<TestRedaction>::write_args(&mut buffer, args.iter().map(|arg| arg.to_string())).expect("To write");
+        assert_eq!(buffer, &b"\nArguments: \"app\" \"--password\" \"***\" \"--token=***\" \"--user\" \"douman\""[..]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_write_memory() {
+        use super::Memory;
+
+        let mut buffer = Vec::new();
+        Memory::write_in(&mut buffer).expect("To write");
+        let output = String::from_utf8(buffer).expect("UTF-8");
+
+        assert!(output.starts_with("\nMemory: rss="));
+        assert!(!output.contains("unknown"));
+    }
+}
@@ -0,0 +1,94 @@
+//! Marker of panic's severity.
+
+use std::env;
+use std::io;
+
+use super::Prefix;
+use super::color::is_color_enabled;
+
+///Marker written by [Severity](struct.Severity.html) when terminal supports emoji.
+pub const SEVERITY_ICON: &str = "💥";
+///Marker written by [Severity](struct.Severity.html) as plain-ASCII fallback.
+pub const SEVERITY_ASCII: &str = "[FATAL]";
+
+#[cfg(not(windows))]
+fn is_utf8_locale(locale: &str) -> bool {
+    let locale = locale.to_ascii_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+//Legacy console of Windows cannot render emoji, unlike Windows Terminal and VS Code.
+#[cfg(windows)]
+fn is_unicode_supported() -> bool {
+    env::var_os("WT_SESSION").is_some() || env::var("TERM_PROGRAM").map(|term| term == "vscode").unwrap_or(false)
+}
+
+#[cfg(not(windows))]
+fn is_unicode_supported() -> bool {
+    //Linux's virtual console has no emoji glyphs.
+    if env::var("TERM").map(|term| term == "linux").unwrap_or(false) {
+        return false;
+    }
+
+    ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+                                  .filter_map(|name| env::var(name).ok())
+                                  .find(|locale| !locale.is_empty())
+                                  .map(|locale| is_utf8_locale(&locale))
+                                  .unwrap_or(false)
+}
+
+fn marker(unicode: bool, color: bool) -> (&'static str, &'static str, &'static str) {
+    match (unicode, color) {
+        (true, _) => ("", SEVERITY_ICON, ""),
+        (false, true) => ("\x1b[1;31m", SEVERITY_ASCII, "\x1b[0m"),
+        (false, false) => ("", SEVERITY_ASCII, ""),
+    }
+}
+
+///Writes marker making panic stand out among other lines of console output.
+///
+///As [Prefix](trait.Prefix.html) it goes as `💥 `, falling back to `[FATAL] ` when
+///terminal is unlikely to render emoji (non UTF-8 locale, Linux console or legacy Windows console).
+///Fallback is bold red, unless colors are [disabled](fn.is_color_enabled.html)
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Severity, Simple, WithPrefix};
+///
+///fn main() {
+///    set_panic_message!(WithPrefix<Severity, Simple>);
+///}
+///```
+pub struct Severity;
+
+impl Prefix for Severity {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let unicode = is_unicode_supported();
+        let (start, marker, end) = marker(unicode, !unicode && is_color_enabled());
+        write!(writer, "{}{}{} ", start, marker, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{marker, SEVERITY_ASCII, SEVERITY_ICON};
+
+    #[cfg(not(windows))]
+    #[test]
+    fn should_detect_utf8_locale() {
+        use super::is_utf8_locale;
+
+        assert!(is_utf8_locale("en_US.UTF-8"));
+        assert!(is_utf8_locale("C.utf8"));
+        assert!(!is_utf8_locale("C"));
+    }
+
+    #[test]
+    fn should_choose_marker() {
+        assert_eq!(marker(true, true), ("", SEVERITY_ICON, ""));
+        assert_eq!(marker(false, false), ("", SEVERITY_ASCII, ""));
+        assert_eq!(marker(false, true), ("\x1b[1;31m", SEVERITY_ASCII, "\x1b[0m"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Writer shared with the rest of application.
+
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use lock;
+
+///Writes into `W` owned by application (e.g. logger's file or socket), without reopening it.
+///
+///Report is buffered and written on flush under single lock,
+///so that it is not interleaved with other writes into `W`.
+///Lock is taken even if it is poisoned, as panic might have happened while holding it.
+///
+///As `writer()` has no state, shared writer should be stored at install time in `static`:
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{PanicFormat, Simple, SharedWriter};
+///
+///use std::fs;
+///use std::sync::{Arc, Mutex, OnceLock};
+///
+///static LOG: OnceLock<SharedWriter<fs::File>> = OnceLock::new();
+///
+///struct ToLog;
+///
+///impl PanicFormat for ToLog {
+///    type Writer = SharedWriter<fs::File>;
+///    type Backtrace = Simple;
+///    type Prefix = Simple;
+///    type PanicInfo = Simple;
+///    type Suffix = Simple;
+///
+///    fn writer() -> Self::Writer {
+///        LOG.get().expect("To install log").clone()
+///    }
+///}
+///
+///fn main() {
+///    let file = fs::File::create(std::env::temp_dir().join("app.log")).expect("To create log");
+///    let file = Arc::new(Mutex::new(file));
+///    //Logger keeps `file` for itself.
+///
+///    let _ = LOG.set(SharedWriter::new(file.clone()));
+///    set_panic_message!(ToLog);
+///}
+///```
+pub struct SharedWriter<W: io::Write> {
+    inner: Arc<Mutex<W>>,
+    buffer: Vec<u8>,
+}
+
+impl<W: io::Write> SharedWriter<W> {
+    ///Creates new instance.
+    pub fn new(inner: Arc<Mutex<W>>) -> Self {
+        SharedWriter {
+            inner,
+            buffer: Vec::new(),
+        }
+    }
+
+    ///Returns shared writer.
+    pub fn inner(&self) -> &Arc<Mutex<W>> {
+        &self.inner
+    }
+}
+
+impl<W: io::Write> Clone for SharedWriter<W> {
+    ///Clones handle to shared writer, without buffered data.
+    fn clone(&self) -> Self {
+        SharedWriter {
+            inner: self.inner.clone(),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+impl<W: io::Write> From<Arc<Mutex<W>>> for SharedWriter<W> {
+    #[inline]
+    fn from(inner: Arc<Mutex<W>>) -> Self {
+        Self::new(inner)
+    }
+}
+
+impl<W: io::Write> io::Write for SharedWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let mut inner = lock(&self.inner);
+        let result = inner.write_all(&self.buffer).and(inner.flush());
+        self.buffer.clear();
+        result
+    }
+}
+
+impl<W: io::Write> Drop for SharedWriter<W> {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            let mut inner = lock(&self.inner);
+            let _ = inner.write_all(&self.buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use super::SharedWriter;
+
+    #[test]
+    fn should_write_on_flush() {
+        let shared = Arc::new(Mutex::new(b"log\n".to_vec()));
+        let mut writer = SharedWriter::new(shared.clone());
+
+        writer.write_all(b"lolka").expect("To write");
+        assert_eq!(*shared.lock().unwrap(), b"log\n");
+
+        writer.flush().expect("To flush");
+        assert_eq!(*shared.lock().unwrap(), b"log\nlolka");
+
+        writer.clone().write_all(b"!").expect("To write");
+        assert_eq!(*shared.lock().unwrap(), b"log\nlolka!");
+    }
+}
@@ -0,0 +1,130 @@
+//! Integration with `slog` crate.
+
+use std::io;
+use std::sync::Mutex;
+use PanicHookInfo;
+
+use slog;
+use location;
+use lock;
+use payload;
+use super::{Backtrace, Debug, Empty, PanicFormat};
+
+static LOGGER: Mutex<Option<slog::Logger>> = Mutex::new(None);
+
+///Emits panic as `CRIT` record of registered `slog::Logger`.
+///
+///Record's message is panic's payload, with following key/values:
+///
+///- `file` - File where panic happened, with prefix stripped according to [location](../location/index.html);
+///- `line` - Line where panic happened;
+///- `backtrace` - Backtrace of [Debug](struct.Debug.html), empty unless `backtrace-on` is enabled.
+///
+///Note that panic is not printed until logger is registered.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///extern crate slog;
+///
+///use lazy_panic::formatter::SlogTarget;
+///
+///fn main() {
+///    let logger = slog::Logger::root(slog::Discard, slog::o!("app" => "example"));
+///    SlogTarget::set_logger(logger.clone());
+///    set_panic_message!(SlogTarget);
+///}
+///```
+pub struct SlogTarget;
+
+impl SlogTarget {
+    ///Registers logger to route panics into.
+    pub fn set_logger(logger: slog::Logger) {
+        *lock(&LOGGER) = Some(logger);
+    }
+}
+
+impl PanicFormat for SlogTarget {
+    type Writer = io::Sink;
+    type Backtrace = Debug;
+    type Prefix = Empty;
+    type PanicInfo = Empty;
+    type Suffix = Empty;
+
+    fn writer() -> Self::Writer {
+        io::sink()
+    }
+
+    fn print(info: &PanicHookInfo) {
+        //Logger is cloned to avoid holding lock while drains run.
+        let logger = match lock(&LOGGER).clone() {
+            Some(logger) => logger,
+            None => return,
+        };
+
+        let (file, line) = match info.location() {
+            Some(location) => (location::strip(location.file()), location.line()),
+            None => ("unknown", 0),
+        };
+        let message = payload::to_string(info.payload());
+
+        let mut backtrace = Vec::new();
+        let _ = Self::Backtrace::write_in(&mut backtrace);
+        let backtrace = String::from_utf8_lossy(&backtrace);
+
+        slog::crit!(logger, "{}", message; "file" => file, "line" => line, "backtrace" => backtrace.trim_end());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use slog;
+    use hook::{install, set_thread_format};
+    use formatter::Simple;
+    use super::SlogTarget;
+
+    struct Collector(Arc<Mutex<Vec<String>>>);
+
+    impl slog::Serializer for Collector {
+        fn emit_arguments(&mut self, key: slog::Key, value: &fmt::Arguments) -> slog::Result {
+            self.0.lock().unwrap().push(format!("{}={}", key, value));
+            Ok(())
+        }
+    }
+
+    struct Drain(Arc<Mutex<Vec<String>>>);
+
+    impl slog::Drain for Drain {
+        type Ok = ();
+        type Err = slog::Never;
+
+        fn log(&self, record: &slog::Record, values: &slog::OwnedKVList) -> Result<(), slog::Never> {
+            self.0.lock().unwrap().push(format!("{}: {}", record.level().as_short_str(), record.msg()));
+            let _ = slog::KV::serialize(&record.kv(), record, &mut Collector(self.0.clone()));
+            let _ = slog::KV::serialize(values, record, &mut Collector(self.0.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn should_emit_panic_via_slog() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        SlogTarget::set_logger(slog::Logger::root(Drain(records.clone()), slog::o!("app" => "test")));
+        install::<Simple>();
+
+        let worker = thread::spawn(|| {
+            set_thread_format::<SlogTarget>();
+            panic!("lolka");
+        });
+        assert!(worker.join().is_err());
+
+        let records = records.lock().unwrap();
+        assert!(records.contains(&"CRIT: lolka".to_owned()), "{:?}", records);
+        assert!(records.contains(&"file=src/formatter/slog_target.rs".to_owned()), "{:?}", records);
+        assert!(records.contains(&"app=test".to_owned()), "{:?}", records);
+    }
+}
@@ -0,0 +1,243 @@
+//! System related components.
+
+use std::env;
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+use std::fs;
+use std::io;
+
+use super::{is_deterministic, Fields, Prefix, Suffix};
+
+///Writes host name and name of the user running the process.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[user@host] `
+///
+///As [Fields](trait.Fields.html) it goes as `user` and `host`
+///
+///Unknown values are written as `unknown`.
+pub struct Host;
+
+impl Prefix for Host {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        if is_deterministic() {
+            return writer.write_all(b"[<user>@<host>] ");
+        }
+
+        let user = user_name();
+        let host = host_name();
+        write!(writer, "[{}@{}] ", user.as_deref().unwrap_or("unknown"), host.as_deref().unwrap_or("unknown"))
+    }
+}
+
+impl Fields for Host {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if is_deterministic() {
+            field("user", "<user>");
+            return field("host", "<host>");
+        }
+
+        field("user", user_name().as_deref().unwrap_or("unknown"));
+        field("host", host_name().as_deref().unwrap_or("unknown"));
+    }
+}
+
+///Writes target, OS and architecture information.
+///
+///As [Suffix](trait.Suffix.html) it goes as
+///`\nSystem: os=linux version=Ubuntu 22.04 LTS arch=x86_64 target=x86_64-unknown-linux-gnu`
+///
+///As [Fields](trait.Fields.html) it goes as `os`, `os_version`, `arch` and `target`
+pub struct SystemInfo;
+
+impl SystemInfo {
+    fn version() -> Option<String> {
+        match is_deterministic() {
+            true => Some("<version>".to_owned()),
+            false => os_version(),
+        }
+    }
+}
+
+impl Suffix for SystemInfo {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        write!(writer, "\nSystem: os={} version={} arch={} target={}",
+               env::consts::OS,
+               Self::version().as_deref().unwrap_or("unknown"),
+               env::consts::ARCH,
+               env!("LAZY_PANIC_TARGET"))
+    }
+}
+
+impl Fields for SystemInfo {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        field("os", env::consts::OS);
+        field("os_version", Self::version().as_deref().unwrap_or("unknown"));
+        field("arch", env::consts::ARCH);
+        field("target", env!("LAZY_PANIC_TARGET"));
+    }
+}
+
+///Writes machine and boot identifiers.
+///
+///As [Suffix](trait.Suffix.html) it goes as `\nMachine: machine-id=... boot-id=...`
+///
+///As [Fields](trait.Fields.html) it goes as `machine_id` and `boot_id`
+///
+///Supported on Linux only. On other platforms values are written as `unknown`.
+pub struct MachineId;
+
+impl Suffix for MachineId {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        if is_deterministic() {
+            return writer.write_all(b"\nMachine: machine-id=<machine-id> boot-id=<boot-id>");
+        }
+
+        let (machine_id, boot_id) = machine_ids();
+        write!(writer, "\nMachine: machine-id={} boot-id={}",
+               machine_id.as_deref().unwrap_or("unknown"),
+               boot_id.as_deref().unwrap_or("unknown"))
+    }
+}
+
+impl Fields for MachineId {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if is_deterministic() {
+            field("machine_id", "<machine-id>");
+            return field("boot_id", "<boot-id>");
+        }
+
+        let (machine_id, boot_id) = machine_ids();
+        field("machine_id", machine_id.as_deref().unwrap_or("unknown"));
+        field("boot_id", boot_id.as_deref().unwrap_or("unknown"));
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn machine_ids() -> (Option<String>, Option<String>) {
+    let machine_id = fs::read_to_string("/etc/machine-id").ok()
+                                                          .or_else(|| fs::read_to_string("/var/lib/dbus/machine-id").ok())
+                                                          .and_then(non_empty);
+    let boot_id = fs::read_to_string("/proc/sys/kernel/random/boot_id").ok().and_then(non_empty);
+
+    (machine_id, boot_id)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn machine_ids() -> (Option<String>, Option<String>) {
+    (None, None)
+}
+
+fn non_empty(value: String) -> Option<String> {
+    let value = value.trim();
+    match value.is_empty() {
+        true => None,
+        false => Some(value.to_owned()),
+    }
+}
+
+#[cfg(windows)]
+fn host_name() -> Option<String> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetComputerNameW(buffer: *mut u16, size: *mut u32) -> i32;
+    }
+
+    //`MAX_COMPUTERNAME_LENGTH` is 15 characters, but it differs across versions.
+    let mut buffer = [0u16; 256];
+    let mut size = buffer.len() as u32;
+    //On success size is set to number of characters, without terminator.
+    match unsafe { GetComputerNameW(buffer.as_mut_ptr(), &mut size) } {
+        0 => None,
+        _ => non_empty(String::from_utf16_lossy(&buffer[..size as usize])),
+    }
+}
+
+#[cfg(unix)]
+fn host_name() -> Option<String> {
+    use std::os::raw::{c_char, c_int};
+
+    extern "C" {
+        fn gethostname(name: *mut c_char, len: usize) -> c_int;
+    }
+
+    //`HOST_NAME_MAX` is 255 bytes on Linux and the BSDs.
+    let mut buffer = [0u8; 256];
+    if unsafe { gethostname(buffer.as_mut_ptr() as *mut c_char, buffer.len()) } != 0 {
+        return None;
+    }
+
+    //Name might be truncated without terminator.
+    let len = buffer.iter().position(|byte| *byte == 0).unwrap_or(buffer.len());
+    non_empty(String::from_utf8_lossy(&buffer[..len]).into_owned())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn host_name() -> Option<String> {
+    env::var("HOSTNAME").ok().and_then(non_empty)
+}
+
+#[cfg(windows)]
+fn user_name() -> Option<String> {
+    env::var("USERNAME").ok().and_then(non_empty)
+}
+
+#[cfg(not(windows))]
+fn user_name() -> Option<String> {
+    env::var("USER").ok().and_then(non_empty).or_else(|| env::var("LOGNAME").ok().and_then(non_empty))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn os_version() -> Option<String> {
+    let release = fs::read_to_string("/etc/os-release").ok().and_then(|release| {
+        release.lines()
+               .find(|line| line.starts_with("PRETTY_NAME="))
+               .map(|line| line["PRETTY_NAME=".len()..].trim_matches('"').to_owned())
+    });
+
+    release.or_else(|| fs::read_to_string("/proc/sys/kernel/osrelease").ok()).and_then(non_empty)
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> Option<String> {
+    const KEY: &str = "<key>ProductVersion</key>";
+
+    let plist = fs::read_to_string("/System/Library/CoreServices/SystemVersion.plist").ok()?;
+    let value = &plist[plist.find(KEY)? + KEY.len()..];
+    let value = &value[value.find("<string>")? + "<string>".len()..];
+    non_empty(value[..value.find("</string>")?].to_owned())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn os_version() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::{Suffix, SystemInfo};
+
+    #[test]
+    fn should_write_system_info() {
+        let mut buffer = Vec::new();
+        SystemInfo::write_in(&mut buffer).expect("To write");
+        let output = String::from_utf8(buffer).expect("UTF-8");
+
+        assert!(output.starts_with(&format!("\nSystem: os={} version=", env::consts::OS)));
+        assert!(output.ends_with(&format!("target={}", env!("LAZY_PANIC_TARGET"))));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_get_host_name() {
+        use std::fs;
+        use super::host_name;
+
+        let expected = fs::read_to_string("/proc/sys/kernel/hostname").expect("To read host name");
+        assert_eq!(host_name().as_deref(), Some(expected.trim()));
+    }
+}
@@ -0,0 +1,114 @@
+//! Conformance checks for custom formats.
+//!
+//!```rust
+//!use lazy_panic::formatter::{JustError, testkit};
+//!
+//!testkit::check::<JustError>();
+//!```
+
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use test::format_panic;
+use super::{Deterministic, Empty, PanicFormat, Section};
+
+struct Unit;
+
+//Writes only Suffix of `F`.
+struct SuffixOf<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> PanicFormat for SuffixOf<F> {
+    type Writer = F::Writer;
+    type Backtrace = Empty;
+    type Prefix = Empty;
+    type PanicInfo = Empty;
+    type Suffix = F::Suffix;
+
+    #[inline]
+    fn writer() -> Self::Writer {
+        F::writer()
+    }
+}
+
+///Writes panics with various payloads via components of `F` and asserts that report ends with Suffix.
+///
+///If Suffix is not the last of `SECTIONS` (e.g. with `TWO_STAGE`), it is only asserted to be present.
+///If it is omitted, only absence of panic is checked.
+///
+///Payloads are `&str`, `String`, `Cow<str>`, unit struct, huge string and strings with
+///replacement, control and bidirectional characters.
+///Output is written in [deterministic](../fn.set_deterministic.html) mode,
+///so that Suffix is the same for every panic.
+///Hence Suffix, which depends on panic via `write_in_with`, is not supported.
+///
+///Note that panic within format aborts process, which fails test as well.
+pub fn check<F: PanicFormat>() {
+    let suffix = format_panic::<Deterministic<SuffixOf<F>>, _>(());
+
+    let reports = [
+        ("&str", format_panic::<Deterministic<F>, _>("lolka")),
+        ("String", format_panic::<Deterministic<F>, _>("lolka".to_owned())),
+        ("Cow<str>", format_panic::<Deterministic<F>, _>(Cow::Borrowed("lolka"))),
+        ("unit struct", format_panic::<Deterministic<F>, _>(Unit)),
+        ("huge string", format_panic::<Deterministic<F>, _>("lolka".repeat(200_000))),
+        ("replacement characters", format_panic::<Deterministic<F>, _>(String::from_utf8_lossy(b"lol\xff\xfeka").into_owned())),
+        ("control characters", format_panic::<Deterministic<F>, _>("lol\0ka\r\n\x1b[31m\u{202e}\u{feff}")),
+    ];
+
+    let is_last = F::SECTIONS.iter().rev().find(|section| **section != Section::Flush) == Some(&Section::Suffix);
+    let is_written = F::SECTIONS.contains(&Section::Suffix);
+    for &(payload, ref report) in reports.iter() {
+        match (is_last, is_written) {
+            (true, _) => assert!(report.ends_with(&suffix), "Report of {} payload doesn't end with Suffix {:?}:\n{}", payload, suffix, report),
+            (false, true) => assert!(report.contains(&suffix), "Report of {} payload doesn't contain Suffix {:?}:\n{}", payload, suffix, report),
+            (false, false) => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Debug, Empty, PanicFormat, Pid, Simple, SystemInfo};
+    use super::check;
+
+    struct Format;
+
+    impl PanicFormat for Format {
+        type Writer = io::Sink;
+        type Backtrace = Debug;
+        type Prefix = Pid;
+        type PanicInfo = Simple;
+        type Suffix = SystemInfo;
+
+        fn writer() -> Self::Writer {
+            io::sink()
+        }
+    }
+
+    struct TwoStage;
+
+    impl PanicFormat for TwoStage {
+        type Writer = io::Sink;
+        type Backtrace = Debug;
+        type Prefix = Pid;
+        type PanicInfo = Simple;
+        type Suffix = SystemInfo;
+
+        const TWO_STAGE: bool = true;
+
+        fn writer() -> Self::Writer {
+            io::sink()
+        }
+    }
+
+    #[test]
+    fn should_check_format() {
+        check::<Empty>();
+        check::<Format>();
+        check::<TwoStage>();
+    }
+}
@@ -0,0 +1,53 @@
+//! Components writing constant text.
+
+use std::io;
+use std::marker::PhantomData;
+
+use super::{Prefix, Suffix};
+
+///Describes constant text, written by [ConstPrefix](struct.ConstPrefix.html) and [ConstSuffix](struct.ConstSuffix.html)
+///
+///Can be defined via [define_text!](../macro.define_text.html)
+pub trait Text {
+    ///Text to write.
+    const TEXT: &'static str;
+}
+
+///Writes `T::TEXT` as [Prefix](trait.Prefix.html)
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Composite, ConstPrefix, Debug, Simple, StderrWriter};
+///
+///define_text!(Daemon = "[my-daemon] ");
+///
+///type MyFormat = Composite<ConstPrefix<Daemon>, Simple, Simple, Debug, StderrWriter>;
+///
+///fn main() {
+///    set_panic_message!(MyFormat);
+///}
+///```
+pub struct ConstPrefix<T> {
+    _text: PhantomData<T>,
+}
+
+impl<T: Text> Prefix for ConstPrefix<T> {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        writer.write_all(T::TEXT.as_bytes())
+    }
+}
+
+///Writes `T::TEXT` as [Suffix](trait.Suffix.html)
+pub struct ConstSuffix<T> {
+    _text: PhantomData<T>,
+}
+
+impl<T: Text> Suffix for ConstSuffix<T> {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        writer.write_all(T::TEXT.as_bytes())
+    }
+}
@@ -0,0 +1,106 @@
+//! Thread related components.
+
+use std::io;
+use std::thread;
+
+use context;
+use origin;
+use super::{is_deterministic, Fields, Prefix, Suffix};
+
+///Writes name and id of the current thread.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[thread 'main' (tid 1)] `
+///
+///As [Fields](trait.Fields.html) it goes as `thread` and `tid`
+///
+///Unnamed threads are written as `<unnamed>`, same as default panic hook does.
+///
+///Id is the one assigned by OS, so that it matches debuggers, `top -H` and system logs.
+///On platforms other than Linux, Android, macOS and Windows it is Rust's `ThreadId` instead.
+pub struct Thread;
+
+impl Thread {
+    //Returns name and id of the current thread.
+    fn current() -> (String, String) {
+        let thread = thread::current();
+        let name = thread.name().unwrap_or("<unnamed>").to_owned();
+        let id = match is_deterministic() {
+            true => "<tid>".to_owned(),
+            false => origin::thread_id(),
+        };
+
+        (name, id)
+    }
+}
+
+impl Prefix for Thread {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let (name, id) = Self::current();
+        write!(writer, "[thread '{}' (tid {})] ", name, id)
+    }
+}
+
+impl Fields for Thread {
+    #[inline]
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        let (name, id) = Self::current();
+        field("thread", &name);
+        field("tid", &id);
+    }
+}
+
+///Writes current thread's context, created by [context!](../macro.context.html)
+///
+///As [Suffix](trait.Suffix.html) it goes as `\nContext:\n  - processing order 1\n  - validating item 2`
+///
+///Writes nothing if context is empty.
+pub struct Context;
+
+impl Suffix for Context {
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        let entries = context::entries();
+        if entries.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_all(b"\nContext:")?;
+        for entry in entries {
+            write!(writer, "\n  - {}", entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::{Prefix, Suffix, Thread, Context};
+
+    #[test]
+    fn should_write_thread_name() {
+        let output = thread::Builder::new().name("worker-3".to_owned()).spawn(|| {
+            let mut buffer = Vec::new();
+            Thread::write_in(&mut buffer).expect("To write");
+            String::from_utf8(buffer).expect("UTF-8")
+        }).expect("To spawn").join().expect("To join");
+
+        assert!(output.starts_with("[thread 'worker-3' (tid "));
+        assert!(output.ends_with(")] "));
+    }
+
+    #[test]
+    fn should_write_context() {
+        let _order = context!("processing order {}", 1);
+        {
+            let _item = context!("validating item {}", 2);
+        }
+        let _total = context!("calculating total");
+
+        let mut buffer = Vec::new();
+        Context::write_in(&mut buffer).expect("To write");
+        assert_eq!(buffer, &b"\nContext:\n  - processing order 1\n  - calculating total"[..]);
+    }
+}
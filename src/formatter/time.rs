@@ -0,0 +1,185 @@
+//! Time related components.
+
+use std::io;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use hook;
+use super::{is_deterministic, Fields, Prefix};
+
+///Writes current UTC time in RFC3339 format.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[2018-01-30T12:00:00.000Z] `
+///
+///As [Fields](trait.Fields.html) it goes as `timestamp`
+pub struct Timestamp;
+
+impl Prefix for Timestamp {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        if is_deterministic() {
+            return writer.write_all(b"[<timestamp>] ");
+        }
+
+        writer.write_all(b"[")?;
+        write_rfc3339(writer, since_epoch())?;
+        writer.write_all(b"] ")
+    }
+}
+
+impl Fields for Timestamp {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if is_deterministic() {
+            return field("timestamp", "<timestamp>");
+        }
+
+        let mut time = Vec::new();
+        let _ = write_rfc3339(&mut time, since_epoch());
+        field("timestamp", &String::from_utf8_lossy(&time));
+    }
+}
+
+///Writes current time as number of seconds since UNIX epoch.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[1517313600.000] `
+///
+///As [Fields](trait.Fields.html) it goes as `timestamp`
+pub struct EpochTimestamp;
+
+impl Prefix for EpochTimestamp {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        if is_deterministic() {
+            return writer.write_all(b"[<timestamp>] ");
+        }
+
+        let time = since_epoch();
+        write!(writer, "[{}.{:03}] ", time.as_secs(), time.subsec_millis())
+    }
+}
+
+impl Fields for EpochTimestamp {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        if is_deterministic() {
+            return field("timestamp", "<timestamp>");
+        }
+
+        let time = since_epoch();
+        field("timestamp", &format!("{}.{:03}", time.as_secs(), time.subsec_millis()));
+    }
+}
+
+///Writes time elapsed since hook installation.
+///
+///As [Prefix](trait.Prefix.html) it goes as `[up 1d 2h 3m 4.567s] `
+///
+///As [Fields](trait.Fields.html) it goes as `uptime`
+///
+///Writes nothing if hook is not installed by this crate.
+pub struct Uptime;
+
+impl Prefix for Uptime {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match hook::installed_at() {
+            Some(_) if is_deterministic() => writer.write_all(b"[up <uptime>] "),
+            Some(installed_at) => {
+                writer.write_all(b"[up ")?;
+                write_duration(writer, Instant::now().duration_since(installed_at))?;
+                writer.write_all(b"] ")
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+impl Fields for Uptime {
+    fn fields<F: FnMut(&str, &str)>(field: &mut F) {
+        match hook::installed_at() {
+            Some(_) if is_deterministic() => field("uptime", "<uptime>"),
+            Some(installed_at) => {
+                let mut uptime = Vec::new();
+                let _ = write_duration(&mut uptime, Instant::now().duration_since(installed_at));
+                field("uptime", &String::from_utf8_lossy(&uptime));
+            },
+            None => (),
+        }
+    }
+}
+
+#[inline]
+fn since_epoch() -> Duration {
+    //Clock set before 1970 is not worth failing panic report.
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+}
+
+fn write_rfc3339<W: io::Write>(writer: &mut W, time: Duration) -> io::Result<()> {
+    const SECS_PER_DAY: u64 = 86_400;
+
+    let secs = time.as_secs();
+    let (year, month, day) = civil_from_days((secs / SECS_PER_DAY) as i64);
+    let secs = secs % SECS_PER_DAY;
+
+    write!(writer, "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+           year, month, day, secs / 3600, secs / 60 % 60, secs % 60, time.subsec_millis())
+}
+
+fn write_duration<W: io::Write>(writer: &mut W, time: Duration) -> io::Result<()> {
+    let secs = time.as_secs();
+    let (days, hours, minutes) = (secs / 86_400, secs / 3600 % 24, secs / 60 % 60);
+
+    if days > 0 {
+        write!(writer, "{}d ", days)?;
+    }
+    if days > 0 || hours > 0 {
+        write!(writer, "{}h ", hours)?;
+    }
+    if secs >= 60 {
+        write!(writer, "{}m ", minutes)?;
+    }
+    write!(writer, "{}.{:03}s", secs % 60, time.subsec_millis())
+}
+
+//Converts number of days since UNIX epoch into (year, month, day).
+//Algorithm is taken from http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = (days - era * 146_097) as u64;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_part = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_part + 2) / 5 + 1) as u32;
+    let month = if month_part < 10 { month_part + 3 } else { month_part - 9 } as u32;
+    let year = year_of_era as i64 + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{write_rfc3339, write_duration};
+
+    #[test]
+    fn should_write_rfc3339() {
+        let mut buffer = Vec::new();
+        write_rfc3339(&mut buffer, Duration::from_millis(951_827_696_789)).expect("To write");
+        assert_eq!(buffer, b"2000-02-29T12:34:56.789Z");
+
+        buffer.clear();
+        write_rfc3339(&mut buffer, Duration::from_secs(0)).expect("To write");
+        assert_eq!(buffer, b"1970-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn should_write_duration() {
+        let mut buffer = Vec::new();
+        write_duration(&mut buffer, Duration::from_millis(93_784_567)).expect("To write");
+        assert_eq!(buffer, b"1d 2h 3m 4.567s");
+
+        buffer.clear();
+        write_duration(&mut buffer, Duration::from_millis(4_567)).expect("To write");
+        assert_eq!(buffer, b"4.567s");
+    }
+}
@@ -0,0 +1,117 @@
+//! Integration with `tracing` crate.
+
+use std::io;
+use PanicHookInfo;
+
+use tracing;
+use location;
+use payload;
+use super::{Backtrace, Debug, Empty, PanicFormat};
+
+///Records panic as `tracing` event of `ERROR` level with target `panic`.
+///
+///Event carries following fields:
+///
+///- `message` - Panic's payload;
+///- `file` - File where panic happened, with prefix stripped according to [location](../location/index.html);
+///- `line` - Line where panic happened;
+///- `backtrace` - Backtrace of [Debug](struct.Debug.html), empty unless `backtrace-on` is enabled.
+///
+///Event is recorded within current span of panicking thread, so that crash can be correlated with
+///in-flight work.
+pub struct TracingEvent;
+
+impl PanicFormat for TracingEvent {
+    type Writer = io::Sink;
+    type Backtrace = Debug;
+    type Prefix = Empty;
+    type PanicInfo = Empty;
+    type Suffix = Empty;
+
+    fn writer() -> Self::Writer {
+        io::sink()
+    }
+
+    fn print(info: &PanicHookInfo) {
+        let (file, line) = match info.location() {
+            Some(location) => (location::strip(location.file()), location.line()),
+            None => ("unknown", 0),
+        };
+        let message = payload::to_string(info.payload());
+
+        let mut backtrace = Vec::new();
+        let _ = Self::Backtrace::write_in(&mut backtrace);
+        let backtrace = String::from_utf8_lossy(&backtrace);
+
+        tracing::error!(target: "panic", file, line, backtrace = %backtrace.trim_end(), "{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    use tracing;
+    use tracing::field::{Field, Visit};
+    use tracing::span;
+    use hook::{install, set_thread_format};
+    use formatter::Simple;
+    use super::TracingEvent;
+
+    #[derive(Clone, Default)]
+    struct Collector {
+        fields: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Visit for Collector {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.fields.lock().unwrap().push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl tracing::Subscriber for Collector {
+        fn enabled(&self, _: &tracing::Metadata) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record) {
+        }
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {
+        }
+
+        fn event(&self, event: &tracing::Event) {
+            let mut visitor = self.clone();
+            event.record(&mut visitor);
+        }
+
+        fn enter(&self, _: &span::Id) {
+        }
+
+        fn exit(&self, _: &span::Id) {
+        }
+    }
+
+    #[test]
+    fn should_record_panic_event() {
+        install::<Simple>();
+        let collector = Collector::default();
+        let fields = collector.fields.clone();
+
+        let worker = thread::spawn(move || {
+            set_thread_format::<TracingEvent>();
+            tracing::subscriber::with_default(collector, || panic!("lolka"));
+        });
+        assert!(worker.join().is_err());
+
+        let fields = fields.lock().unwrap();
+        assert!(fields.contains(&"message=lolka".to_owned()), "{:?}", fields);
+        assert!(fields.contains(&"file=\"src/formatter/tracing_event.rs\"".to_owned()), "{:?}", fields);
+    }
+}
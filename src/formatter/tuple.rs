@@ -0,0 +1,86 @@
+//! Composition of components via tuples.
+
+use std::io;
+use PanicHookInfo;
+
+use super::{Backtrace, Fields, Prefix, Suffix};
+
+macro_rules! impl_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Prefix),+> Prefix for ($($name,)+) {
+            #[inline]
+            fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+                let result = Ok(());
+                $(let result = result.and($name::write_in(writer));)+
+                result
+            }
+
+            #[inline]
+            fn write_in_with<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+                let result = Ok(());
+                $(let result = result.and($name::write_in_with(writer, info));)+
+                result
+            }
+        }
+
+        impl<$($name: Suffix),+> Suffix for ($($name,)+) {
+            #[inline]
+            fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+                let result = Ok(());
+                $(let result = result.and($name::write_in(writer));)+
+                result
+            }
+
+            #[inline]
+            fn write_in_with<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+                let result = Ok(());
+                $(let result = result.and($name::write_in_with(writer, info));)+
+                result
+            }
+        }
+
+        impl<$($name: Backtrace),+> Backtrace for ($($name,)+) {
+            #[inline]
+            fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+                let result = Ok(());
+                $(let result = result.and($name::write_in(writer));)+
+                result
+            }
+        }
+
+        impl<$($name: Fields),+> Fields for ($($name,)+) {
+            #[inline]
+            fn fields<FN: FnMut(&str, &str)>(field: &mut FN) {
+                $($name::fields(field);)+
+            }
+        }
+    }
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
+impl_tuple!(A, B, C, D, E, F, G);
+impl_tuple!(A, B, C, D, E, F, G, H);
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Composite, ConstPrefix, ConstSuffix, Empty, JustError};
+
+    define_text!(Open = "[");
+    define_text!(Tag = "tag");
+    define_text!(Close = "] ");
+    define_text!(Dot = ".");
+
+    #[test]
+    fn should_write_tuple_in_order() {
+        type Format = Composite<(ConstPrefix<Open>, ConstPrefix<Tag>, ConstPrefix<Close>), JustError, (ConstSuffix<Dot>, Empty, ConstSuffix<Dot>), (Empty, Empty), io::Sink>;
+
+        assert_eq!(::test::format_panic::<Format, _>("lolka"), "[tag] lolka..");
+    }
+}
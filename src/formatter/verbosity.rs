@@ -0,0 +1,142 @@
+//! Level of details chosen at panic time.
+
+use std::env;
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use PanicHookInfo;
+
+use super::{Backtrace, Debug, PanicFormat, PanicInfo, Prefix, Simple, Suffix};
+
+///Environment variable with level of [Leveled](struct.Leveled.html), from `0` to `3`
+pub const VERBOSITY_ENV: &str = "LAZY_PANIC_VERBOSITY";
+///Level used when neither set programmatically nor via environment.
+pub const DEFAULT_VERBOSITY: u8 = 2;
+
+const UNSET: usize = usize::MAX;
+
+static VERBOSITY: AtomicUsize = AtomicUsize::new(UNSET);
+
+fn parse(value: &str) -> Option<u8> {
+    match value.trim().parse::<u8>() {
+        Ok(level) => Some(level.min(3)),
+        Err(_) => None,
+    }
+}
+
+///Sets level of [Leveled](struct.Leveled.html), overriding environment.
+///
+///Levels above `3` are the same as `3`.
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level as usize, Ordering::Release);
+}
+
+///Returns level of [Leveled](struct.Leveled.html)
+///
+///Level set via [set_verbosity](fn.set_verbosity.html) takes priority over [VERBOSITY_ENV](constant.VERBOSITY_ENV.html),
+///otherwise [DEFAULT_VERBOSITY](constant.DEFAULT_VERBOSITY.html)
+pub fn verbosity() -> u8 {
+    match VERBOSITY.load(Ordering::Acquire) {
+        UNSET => env::var(VERBOSITY_ENV).ok().and_then(|value| parse(&value)).unwrap_or(DEFAULT_VERBOSITY),
+        level => (level as u8).min(3),
+    }
+}
+
+///Writes panic with level of details chosen at panic time via [verbosity](fn.verbosity.html)
+///
+///- `0` - only panic's message, same as [JustError](struct.JustError.html) without metadata;
+///- `1` - `Panic: ` followed by location and message;
+///- `2` - also metadata and context, same as [Simple](struct.Simple.html);
+///- `3` - also backtrace, same as [Debug](struct.Debug.html).
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::Leveled;
+///
+///fn main() {
+///    //Can be overridden by LAZY_PANIC_VERBOSITY=3 as long as not set programmatically.
+///    set_panic_message!(Leveled);
+///}
+///```
+pub struct Leveled;
+
+impl Backtrace for Leveled {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match verbosity() {
+            3 => <Debug as Backtrace>::write_in(writer),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Prefix for Leveled {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match verbosity() {
+            0 => Ok(()),
+            _ => <Simple as Prefix>::write_in(writer),
+        }
+    }
+}
+
+impl PanicInfo for Leveled {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        match verbosity() {
+            0 => write_payload_default!(writer, info.payload()),
+            _ => <Simple as PanicInfo>::write_in(writer, info),
+        }
+    }
+}
+
+impl Suffix for Leveled {
+    #[inline]
+    fn write_in<W: io::Write>(writer: &mut W) -> io::Result<()> {
+        match verbosity() {
+            0 | 1 => writeln!(writer),
+            _ => <Simple as Suffix>::write_in(writer),
+        }
+    }
+}
+
+impl PanicFormat for Leveled {
+    type Writer = io::BufWriter<io::Stderr>;
+    type Backtrace = Self;
+    type Prefix = Self;
+    type PanicInfo = Self;
+    type Suffix = Self;
+
+    fn writer() -> Self::Writer {
+        io::BufWriter::new(io::stderr())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use test::format_panic;
+    use super::{parse, set_verbosity, verbosity, Leveled};
+
+    #[test]
+    fn should_parse_verbosity() {
+        assert_eq!(parse("0"), Some(0));
+        assert_eq!(parse(" 3\n"), Some(3));
+        assert_eq!(parse("9"), Some(3));
+        assert_eq!(parse("verbose"), None);
+    }
+
+    #[test]
+    fn should_write_by_verbosity() {
+        set_verbosity(0);
+        assert_eq!(format_panic::<Leveled, _>("lolka"), "lolka\n");
+
+        set_verbosity(1);
+        let report = format_panic::<Leveled, _>("lolka");
+        assert!(report.starts_with("Panic: src/hook/test.rs:"), "{}", report);
+        assert!(report.ends_with(" - lolka\n"), "{}", report);
+
+        set_verbosity(7);
+        assert_eq!(verbosity(), 3);
+    }
+}
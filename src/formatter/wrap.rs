@@ -0,0 +1,243 @@
+//! Wrapping of long lines to terminal's width.
+
+use std::env;
+use std::io;
+use std::marker::PhantomData;
+use PanicHookInfo;
+
+use super::{PanicFormat, Section};
+
+///Hanging indentation of continuation lines, written by [Wrapped](struct.Wrapped.html)
+pub const INDENT: &str = "    ";
+
+#[derive(Clone, Copy)]
+enum Escape {
+    None,
+    Start,
+    Csi,
+}
+
+///Writer breaking lines longer than `width` columns, with `indent` before each continuation line.
+///
+///Characters are counted as single column, while ANSI escape sequences are not counted.
+///Width of `0` (or not greater than indent) disables wrapping.
+pub struct WrapWriter<W> {
+    writer: W,
+    width: usize,
+    indent: &'static str,
+    column: usize,
+    escape: Escape,
+}
+
+impl<W: io::Write> WrapWriter<W> {
+    ///Creates new instance.
+    pub fn new(writer: W, width: usize, indent: &'static str) -> Self {
+        WrapWriter {
+            writer,
+            width: match width > indent.len() {
+                true => width,
+                false => 0,
+            },
+            indent,
+            column: 0,
+            escape: Escape::None,
+        }
+    }
+
+    ///Returns underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: io::Write> io::Write for WrapWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.width == 0 {
+            self.writer.write_all(buf)?;
+            return Ok(buf.len());
+        }
+
+        let mut start = 0;
+        for (idx, &byte) in buf.iter().enumerate() {
+            match (self.escape, byte) {
+                (Escape::Start, b'[') => {
+                    self.escape = Escape::Csi;
+                    continue;
+                },
+                (Escape::Start, _) => self.escape = Escape::None,
+                (Escape::Csi, 0x40..=0x7e) => {
+                    self.escape = Escape::None;
+                    continue;
+                },
+                (Escape::Csi, _) => continue,
+                (Escape::None, _) => (),
+            }
+
+            match byte {
+                0x1b => {
+                    self.escape = Escape::Start;
+                    continue;
+                },
+                b'\n' | b'\r' => {
+                    self.column = 0;
+                    continue;
+                },
+                //UTF-8 continuation byte, counted with its leading byte.
+                0x80..=0xbf => continue,
+                _ => (),
+            }
+
+            if self.column >= self.width {
+                self.writer.write_all(&buf[start..idx])?;
+                self.writer.write_all(b"\n")?;
+                self.writer.write_all(self.indent.as_bytes())?;
+                self.column = self.indent.len();
+                start = idx;
+            }
+            self.column += 1;
+        }
+
+        self.writer.write_all(&buf[start..])?;
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos"))]
+fn stderr_width() -> Option<usize> {
+    use std::os::raw::{c_int, c_ulong};
+
+    #[repr(C)]
+    struct WinSize {
+        row: u16,
+        col: u16,
+        xpixel: u16,
+        ypixel: u16,
+    }
+
+    #[cfg(target_os = "macos")]
+    const TIOCGWINSZ: c_ulong = 0x4008_7468;
+    #[cfg(not(target_os = "macos"))]
+    const TIOCGWINSZ: c_ulong = 0x5413;
+
+    extern "C" {
+        fn ioctl(fd: c_int, request: c_ulong, ...) -> c_int;
+    }
+
+    let mut size = WinSize {
+        row: 0,
+        col: 0,
+        xpixel: 0,
+        ypixel: 0,
+    };
+    //Fails unless stderr is terminal.
+    match unsafe { ioctl(2, TIOCGWINSZ, &mut size as *mut WinSize) } {
+        0 if size.col > 0 => Some(size.col as usize),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn stderr_width() -> Option<usize> {
+    None
+}
+
+///Returns width of terminal, as columns.
+///
+///`COLUMNS` environment variable takes priority, otherwise width of stderr's terminal is queried
+///on Linux, Android and macOS.
+///Returns `None` if stderr is not terminal or width is unknown.
+pub fn terminal_width() -> Option<usize> {
+    match env::var("COLUMNS").ok().and_then(|columns| columns.trim().parse::<usize>().ok()) {
+        Some(columns) if columns > 0 => Some(columns),
+        _ => stderr_width(),
+    }
+}
+
+///Wraps lines of `F`'s report (e.g. long message or backtrace) to [terminal_width](fn.terminal_width.html)
+///
+///Continuation lines start with [INDENT](constant.INDENT.html).
+///Nothing is wrapped if width is unknown, e.g. when stderr is redirected to file.
+///
+///Note that `F`'s own `print` is not used, so wrap formatters like [Abort](struct.Abort.html) on the outside.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Debug, Wrapped};
+///
+///fn main() {
+///    set_panic_message!(Wrapped<Debug>);
+///}
+///```
+pub struct Wrapped<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> PanicFormat for Wrapped<F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    const TWO_STAGE: bool = F::TWO_STAGE;
+    const SECTIONS: &'static [Section] = F::SECTIONS;
+
+    #[inline]
+    fn writer() -> Self::Writer {
+        F::writer()
+    }
+
+    #[inline]
+    fn on_write_error(info: &PanicHookInfo, error: io::Error) {
+        F::on_write_error(info, error)
+    }
+
+    #[inline]
+    fn finalize(writer: Self::Writer) -> io::Result<()> {
+        F::finalize(writer)
+    }
+
+    fn write_report<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        let mut writer = WrapWriter::new(writer, terminal_width().unwrap_or(0), INDENT);
+        F::write_report(&mut writer, info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::WrapWriter;
+
+    fn wrap(width: usize, parts: &[&str]) -> String {
+        let mut writer = WrapWriter::new(Vec::new(), width, "  ");
+        for part in parts {
+            writer.write_all(part.as_bytes()).expect("To write");
+        }
+        String::from_utf8(writer.into_inner()).expect("UTF-8")
+    }
+
+    #[test]
+    fn should_wrap_long_lines() {
+        assert_eq!(wrap(6, &["short\n", "0123456789abcd"]), "short\n012345\n  6789\n  abcd");
+        assert_eq!(wrap(6, &["0123", "456", "7\n01"]), "012345\n  67\n01");
+    }
+
+    #[test]
+    fn should_count_characters_without_escapes() {
+        assert_eq!(wrap(4, &["\x1b[31mäöüß\x1b[0mab"]), "\x1b[31mäöüß\x1b[0m\n  ab");
+    }
+
+    #[test]
+    fn should_not_wrap_without_width() {
+        assert_eq!(wrap(0, &["0123456789"]), "0123456789");
+        assert_eq!(wrap(2, &["0123456789"]), "0123456789");
+    }
+}
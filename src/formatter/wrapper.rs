@@ -0,0 +1,464 @@
+//! Formatters wrapping other formatters.
+
+use std::env;
+use std::io;
+use std::marker::PhantomData;
+use std::process;
+use PanicHookInfo;
+
+use exit_code;
+use hook;
+use notify::Notifier;
+use super::{PanicFormat, Prefix, Section, Pid, Thread, Timestamp, THREAD_DETERMINISTIC};
+
+//Forwards everything but `print` to `F`, so that wrapper writes same report as `F`.
+macro_rules! delegate_format {
+    ($format:ident) => {
+        const TWO_STAGE: bool = $format::TWO_STAGE;
+        const SECTIONS: &'static [Section] = $format::SECTIONS;
+
+        #[inline]
+        fn writer() -> Self::Writer {
+            $format::writer()
+        }
+
+        #[inline]
+        fn on_write_error(info: &PanicHookInfo, error: io::Error) {
+            $format::on_write_error(info, error)
+        }
+
+        #[inline]
+        fn finalize(writer: Self::Writer) -> io::Result<()> {
+            $format::finalize(writer)
+        }
+    };
+    ($format:ident, write_report) => {
+        delegate_format!($format);
+
+        #[inline]
+        fn write_report<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+            $format::write_report(writer, info)
+        }
+    };
+}
+
+///Aborts process after `F` prints panic.
+///
+///Useful for services where unwinding after panic (e.g. with poisoned state)
+///is worse than dying immediately.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Abort, Simple};
+///
+///fn main() {
+///    set_panic_message!(Abort<Simple>);
+///}
+///```
+pub struct Abort<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> PanicFormat for Abort<F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F, write_report);
+
+    fn print(info: &PanicHookInfo) {
+        //`F::print` finalizes writer, so report is complete before abort.
+        F::print(info);
+        process::abort();
+    }
+}
+
+///Exits process with `CODE` after `F` prints panic.
+///
+///Lets orchestration (e.g. systemd or Kubernetes) distinguish panic from clean shutdown
+///by exit code alone.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Exit, Simple};
+///
+///fn main() {
+///    set_panic_message!(Exit<Simple, 70>);
+///}
+///```
+pub struct Exit<F, const CODE: i32> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat, const CODE: i32> PanicFormat for Exit<F, CODE> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F, write_report);
+
+    fn print(info: &PanicHookInfo) {
+        F::print(info);
+        process::exit(CODE);
+    }
+}
+
+///Exits process with code chosen by [exit_code](../exit_code/index.html) after `F` prints panic.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{ExitMapped, Simple};
+///
+///pub struct ConfigMissing;
+///
+///fn main() {
+///    //EX_CONFIG
+///    lazy_panic::exit_code::register::<ConfigMissing>(78);
+///    set_panic_message!(ExitMapped<Simple>);
+///}
+///```
+pub struct ExitMapped<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> PanicFormat for ExitMapped<F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F, write_report);
+
+    fn print(info: &PanicHookInfo) {
+        F::print(info);
+        process::exit(exit_code::code(info));
+    }
+}
+
+///Invokes default panic hook of standard library after `F` prints panic.
+///
+///Default output includes `RUST_BACKTRACE` support.
+///Requires hook to be installed by this crate, otherwise only `F` prints panic.
+pub struct WithDefault<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> PanicFormat for WithDefault<F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F, write_report);
+
+    fn print(info: &PanicHookInfo) {
+        F::print(info);
+        hook::call_default(info);
+    }
+}
+
+///Aborts process after `F` prints panic, if `LAZY_PANIC_CORE_DUMP` is set to anything but `0`.
+///
+///Abort raises `SIGABRT` on unix, producing core dump (subject to `ulimit -c`) for offline
+///debugging, while report is already written by `F`.
+///
+///Otherwise behaves as `F`.
+pub struct CoreDump<F> {
+    _format: PhantomData<F>,
+}
+
+///Environment variable enabling [CoreDump](struct.CoreDump.html).
+pub const CORE_DUMP_ENV: &str = "LAZY_PANIC_CORE_DUMP";
+
+impl<F: PanicFormat> PanicFormat for CoreDump<F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F, write_report);
+
+    fn print(info: &PanicHookInfo) {
+        F::print(info);
+
+        match env::var_os(CORE_DUMP_ENV) {
+            Some(ref value) if value != "0" => process::abort(),
+            _ => (),
+        }
+    }
+}
+
+///Delivers panic via notifier `N` after `F` prints panic.
+///
+///See [notify](../notify/index.html) for available notifiers.
+pub struct Notify<F, N> {
+    _format: PhantomData<(F, N)>,
+}
+
+impl<F: PanicFormat, N: Notifier> PanicFormat for Notify<F, N> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F, write_report);
+
+    fn print(info: &PanicHookInfo) {
+        F::print(info);
+        N::notify(info);
+    }
+}
+
+///Omits nondeterministic parts of output, while `F` prints panic.
+///
+///See [set_deterministic](fn.set_deterministic.html) for details.
+///
+///```rust
+///use lazy_panic::formatter::{Deterministic, Pid, Simple, PanicFormat};
+///use lazy_panic::test::format_panic;
+///
+///struct Format;
+///
+///impl PanicFormat for Format {
+///    type Writer = <Simple as PanicFormat>::Writer;
+///    type Backtrace = Simple;
+///    type Prefix = Pid;
+///    type PanicInfo = Simple;
+///    type Suffix = Simple;
+///
+///    fn writer() -> Self::Writer {
+///        Simple::writer()
+///    }
+///}
+///
+///assert!(format_panic::<Deterministic<Format>, _>("lolka").starts_with("[pid <pid>] "));
+///```
+pub struct Deterministic<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> Deterministic<F> {
+    fn scoped<R, C: FnOnce() -> R>(closure: C) -> R {
+        let enabled = THREAD_DETERMINISTIC.with(|enabled| enabled.replace(true));
+        let result = closure();
+        THREAD_DETERMINISTIC.with(|thread_enabled| thread_enabled.set(enabled));
+        result
+    }
+}
+
+impl<F: PanicFormat> PanicFormat for Deterministic<F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F);
+
+    fn write_report<W: io::Write>(writer: &mut W, info: &PanicHookInfo) -> io::Result<()> {
+        Self::scoped(|| F::write_report(writer, info))
+    }
+
+    fn print(info: &PanicHookInfo) {
+        Self::scoped(|| F::print(info))
+    }
+}
+
+///Writes Prefix `P` before Prefix of `F`, otherwise same as `F`.
+///
+///Note that `F`'s own `print` is not used, so wrap formatters like [Abort](struct.Abort.html) on the outside,
+///e.g. `Abort<WithTimestamp<Simple>>`
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Simple, WithPid, WithTimestamp};
+///
+///fn main() {
+///    set_panic_message!(WithTimestamp<WithPid<Simple>>);
+///}
+///```
+pub struct WithPrefix<P, F> {
+    _prefix: PhantomData<P>,
+    _format: PhantomData<F>,
+}
+
+impl<P: Prefix, F: PanicFormat> PanicFormat for WithPrefix<P, F> {
+    type Writer = F::Writer;
+    type Backtrace = F::Backtrace;
+    type Prefix = (P, F::Prefix);
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    delegate_format!(F);
+}
+
+///Writes [Timestamp](struct.Timestamp.html) before Prefix of `F`
+pub type WithTimestamp<F> = WithPrefix<Timestamp, F>;
+///Writes [Thread](struct.Thread.html) before Prefix of `F`
+pub type WithThread<F> = WithPrefix<Thread, F>;
+///Writes [Pid](struct.Pid.html) before Prefix of `F`
+pub type WithPid<F> = WithPrefix<Pid, F>;
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io;
+    use std::process;
+
+    use test::format_panic;
+    use formatter::{ConstPrefix, ConstSuffix, Deterministic, Empty, JustError, PanicFormat, Section, CORE_DUMP_ENV};
+    use hook::install;
+    use notify::Notifier;
+    use super::{Abort, CoreDump, Exit, ExitMapped, Notify, WithDefault, WithPid, WithThread, WithTimestamp};
+
+    define_text!(Open = "[");
+    define_text!(Close = "] ");
+
+    struct Reordered;
+
+    impl PanicFormat for Reordered {
+        type Writer = io::Sink;
+        type Backtrace = Empty;
+        type Prefix = ConstPrefix<Open>;
+        type PanicInfo = JustError;
+        type Suffix = ConstSuffix<Close>;
+
+        const SECTIONS: &'static [Section] = &[Section::Suffix, Section::PanicInfo, Section::Prefix];
+
+        fn writer() -> Self::Writer {
+            io::sink()
+        }
+    }
+
+    struct Silent;
+
+    impl Notifier for Silent {
+        fn notify(_: &::PanicHookInfo) {
+        }
+    }
+
+    #[test]
+    fn should_write_report_of_wrapped_format() {
+        let expected = format_panic::<Reordered, _>("lolka");
+        assert_eq!(expected, "] lolka[");
+
+        assert_eq!(format_panic::<Abort<Reordered>, _>("lolka"), expected);
+        assert_eq!(format_panic::<Exit<Reordered, 70>, _>("lolka"), expected);
+        assert_eq!(format_panic::<ExitMapped<Reordered>, _>("lolka"), expected);
+        assert_eq!(format_panic::<WithDefault<Reordered>, _>("lolka"), expected);
+        assert_eq!(format_panic::<CoreDump<Reordered>, _>("lolka"), expected);
+        assert_eq!(format_panic::<Notify<Reordered, Silent>, _>("lolka"), expected);
+    }
+
+    #[test]
+    fn should_write_prefix_before_format() {
+        let report = format_panic::<Deterministic<WithTimestamp<WithPid<WithThread<JustError>>>>, _>("lolka");
+        assert!(report.starts_with("[<timestamp>] [pid <pid>] [thread '"), "{}", report);
+        assert!(report.contains(" (tid <tid>)] lolka"), "{}", report);
+    }
+
+    const WRAPPER_CHILD: &str = "LAZY_PANIC_TEST_WRAPPER_CHILD";
+
+    struct ConfigMissing;
+
+    //Wrappers terminate process, so each one panics in own process.
+    #[test]
+    #[ignore]
+    fn wrapper_child() {
+        match env::var(WRAPPER_CHILD).as_ref().map(String::as_str) {
+            Ok("abort") => install::<Abort<JustError>>(),
+            Ok("exit") => install::<Exit<JustError, 70>>(),
+            Ok("core_dump") => install::<CoreDump<JustError>>(),
+            Ok("with_default") => install::<WithDefault<JustError>>(),
+            Ok("exit_mapped") => {
+                ::exit_code::register::<ConfigMissing>(78);
+                install::<ExitMapped<JustError>>();
+                ::std::panic::panic_any(ConfigMissing);
+            },
+            Ok("exit_mapped_default") => {
+                ::exit_code::register::<ConfigMissing>(78);
+                install::<ExitMapped<JustError>>();
+            },
+            _ => return,
+        }
+
+        panic!("lolka");
+    }
+
+    fn run_child(wrapper: &str, core_dump: &str) -> process::Output {
+        process::Command::new(env::current_exe().expect("To get test executable"))
+                         .args(["formatter::wrapper::tests::wrapper_child", "--exact", "--ignored", "--nocapture", "--test-threads=1"])
+                         .env(WRAPPER_CHILD, wrapper)
+                         .env(CORE_DUMP_ENV, core_dump)
+                         .output()
+                         .expect("To run child")
+    }
+
+    #[cfg(unix)]
+    fn is_aborted(status: process::ExitStatus) -> bool {
+        use std::os::unix::process::ExitStatusExt;
+
+        status.signal() == Some(6)
+    }
+
+    #[cfg(not(unix))]
+    fn is_aborted(status: process::ExitStatus) -> bool {
+        !status.success() && status.code() != Some(101)
+    }
+
+    #[test]
+    fn should_terminate_after_print() {
+        let output = run_child("abort", "0");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(is_aborted(output.status), "{:?}: {}", output.status, stderr);
+        assert!(stderr.starts_with("lolka\n"), "{}", stderr);
+
+        let output = run_child("exit", "0");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(70), "{}", stderr);
+        assert!(stderr.starts_with("lolka\n"), "{}", stderr);
+
+        let output = run_child("core_dump", "1");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(is_aborted(output.status), "{:?}: {}", output.status, stderr);
+        assert!(stderr.starts_with("lolka\n"), "{}", stderr);
+
+        //Without core dump panic unwinds, failing test as usual.
+        let output = run_child("core_dump", "0");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(101), "{}", stderr);
+        assert!(stderr.starts_with("lolka\n"), "{}", stderr);
+
+        let output = run_child("exit_mapped", "0");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(78), "{}", stderr);
+        assert!(stderr.starts_with("Any { .. }\n"), "{}", stderr);
+
+        let output = run_child("exit_mapped_default", "0");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(::exit_code::DEFAULT), "{}", stderr);
+        assert!(stderr.starts_with("lolka\n"), "{}", stderr);
+
+        let output = run_child("with_default", "0");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(101), "{}", stderr);
+        assert!(stderr.starts_with("lolka\n"), "{}", stderr);
+        assert!(stderr.contains("panicked at src/formatter/wrapper.rs:"), "{}", stderr);
+    }
+}
@@ -0,0 +1,769 @@
+//! Panic hook installation and dispatching.
+
+use std::any;
+use std::fmt;
+use std::cell::{Cell, RefCell};
+use std::io::{self, Write};
+use std::panic;
+use std::ptr;
+use std::thread;
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use PanicHookInfo;
+
+use formatter::{Debug, DynPanicFormat, PanicFormat};
+use location;
+use lock;
+use origin::Origin;
+use payload;
+
+pub mod test;
+
+type PrintFn = fn(&PanicHookInfo);
+type Hook = Box<dyn Fn(&PanicHookInfo) + Sync + Send + 'static>;
+
+struct Format {
+    name: fn() -> &'static str,
+    print: PrintFn,
+    report: fn(&PanicHookInfo) -> String,
+}
+
+impl Format {
+    #[inline]
+    fn of<F: PanicFormat>() -> &'static Format {
+        &Format {
+            name: any::type_name::<F>,
+            print: F::print,
+            report: F::format_to_string,
+        }
+    }
+
+    #[inline]
+    fn dynamic() -> &'static Format {
+        &Format {
+            name: dynamic_name,
+            print: print_dynamic,
+            report: report_dynamic,
+        }
+    }
+
+    #[inline]
+    fn preopened<F: PanicFormat>() -> &'static Format {
+        &Format {
+            name: any::type_name::<F>,
+            print: print_preopened::<F>,
+            report: F::format_to_string,
+        }
+    }
+}
+
+static ACTIVE: AtomicPtr<Format> = AtomicPtr::new(ptr::null_mut());
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static INSTALLED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+static PANICS: AtomicUsize = AtomicUsize::new(0);
+static DEFAULT_HOOK: Mutex<Option<Hook>> = Mutex::new(None);
+static WRITER: Mutex<Option<Box<dyn io::Write + Send>>> = Mutex::new(None);
+static DYNAMIC: RwLock<Option<(&'static str, Box<dyn DynPanicFormat>)>> = RwLock::new(None);
+static ABORT_MODE: AtomicBool = AtomicBool::new(cfg!(panic = "abort"));
+
+thread_local!(static THREAD_FORMAT: Cell<Option<&'static Format>> = const { Cell::new(None) });
+thread_local!(static IN_HOOK: Cell<bool> = const { Cell::new(false) });
+thread_local!(static CALLS: RefCell<Option<mpsc::Sender<Message>>> = const { RefCell::new(None) });
+thread_local!(static CAPTURE: Cell<bool> = const { Cell::new(false) });
+thread_local!(static LAST_REPORT: RefCell<Option<String>> = const { RefCell::new(None) });
+thread_local!(static COLLECT: Cell<bool> = const { Cell::new(false) });
+thread_local!(static LAST_PANIC: RefCell<Option<PanicReport>> = const { RefCell::new(None) });
+
+///Installs panic hook that uses `F` to print panics.
+///
+///Threads that registered their own format via [set_thread_format](fn.set_thread_format.html)
+///use it instead of `F`.
+///
+///Panic is formatted on helper thread, acting on behalf of panicking one (e.g. its name, id, context and backtrace),
+///as standard library aborts process on panic within hook.
+///So if component of `F` panics, it is caught and both messages are written to stderr as
+///`panic while formatting panic: {payload} / {nested payload}`
+pub fn install<F: PanicFormat>() {
+    switch_format::<F>();
+    set_hook();
+}
+
+fn set_hook() {
+    //Once current hook is taken, the next one is always the default hook of standard library.
+    drop(panic::take_hook());
+    let default_hook = panic::take_hook();
+    lock(&DEFAULT_HOOK).get_or_insert(default_hook);
+
+    panic::set_hook(Box::new(dispatch));
+    lock(&INSTALLED_AT).get_or_insert_with(Instant::now);
+    INSTALLED.store(true, Ordering::Release);
+}
+
+///Installs panic hook that uses `F` to print panics into `writer`, created beforehand.
+///
+///Unlike [install](fn.install.html), `F::writer()` is not called at panic time,
+///which is the worst moment to open file or connect socket.
+///Writer is kept until next call of this function and flushed after each panic,
+///with errors handled by `F::on_write_error`.
+///Note that `F::print` and `F::finalize` are not used.
+///
+///```rust
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Empty, JustError, PanicFormat, SharedWriter};
+///
+///use std::thread;
+///use std::sync::{Arc, Mutex};
+///
+///struct ToVec;
+///
+///impl PanicFormat for ToVec {
+///    type Writer = SharedWriter<Vec<u8>>;
+///    type Backtrace = Empty;
+///    type Prefix = Empty;
+///    type PanicInfo = JustError;
+///    type Suffix = Empty;
+///
+///    fn writer() -> Self::Writer {
+///        unreachable!()
+///    }
+///}
+///
+///fn main() {
+///    let output = Arc::new(Mutex::new(Vec::new()));
+///    lazy_panic::hook::install_with_writer::<ToVec>(SharedWriter::new(output.clone()));
+///
+///    assert!(thread::spawn(|| panic!("lolka")).join().is_err());
+///    let output = output.lock().unwrap().clone();
+///    assert_eq!(String::from_utf8(output).unwrap(), "lolka");
+///}
+///```
+pub fn install_with_writer<F: PanicFormat>(writer: F::Writer) where F::Writer: Send + 'static {
+    *lock(&WRITER) = Some(Box::new(writer));
+    ACTIVE.store(Format::preopened::<F>() as *const Format as *mut Format, Ordering::Release);
+    set_hook();
+}
+
+///Installs panic hook that uses `format` value to print panics.
+///
+///Unlike [install](fn.install.html), format can carry runtime state.
+///Replaces value of previous call, if any.
+///See [DynPanicFormat](../formatter/trait.DynPanicFormat.html) for example.
+pub fn install_dyn<F: DynPanicFormat + 'static>(format: F) {
+    *DYNAMIC.write().unwrap_or_else(|error| error.into_inner()) = Some((any::type_name::<F>(), Box::new(format)));
+    ACTIVE.store(Format::dynamic() as *const Format as *mut Format, Ordering::Release);
+    set_hook();
+}
+
+///Returns instant when hook has been installed by this crate for the first time.
+pub fn installed_at() -> Option<Instant> {
+    *lock(&INSTALLED_AT)
+}
+
+///Returns whether hook has been installed by this crate.
+///
+///Note that hook cannot be tracked once replaced by `std::panic::set_hook` outside of this crate.
+pub fn is_installed() -> bool {
+    INSTALLED.load(Ordering::Acquire)
+}
+
+///Returns type name of the format used by installed hook, if any.
+pub fn installed_format_name() -> Option<&'static str> {
+    match is_installed() {
+        true => active().map(|format| (format.name)()),
+        false => None,
+    }
+}
+
+///Invokes default panic hook of standard library.
+///
+///Default hook is captured when hook is installed by this crate, otherwise does nothing.
+pub fn call_default(info: &PanicHookInfo) {
+    //Default hook writes backtrace of the current thread, so it is called on panicking one.
+    if !call_on_origin(call_default_here) {
+        call_default_here(info);
+    }
+}
+
+fn call_default_here(info: &PanicHookInfo) {
+    if let Some(hook) = lock(&DEFAULT_HOOK).as_ref() {
+        hook(info);
+    }
+}
+
+///Sets whether hook runs in mode for `panic = "abort"` builds.
+///
+///In this mode panic is formatted on panicking thread and standard streams are flushed before hook returns.
+///So that full report is written before process is aborted.
+///
+///Enabled by default when crate is built with `panic = "abort"`.
+pub fn set_abort_mode(enabled: bool) {
+    ABORT_MODE.store(enabled, Ordering::Release);
+}
+
+///Returns whether hook runs in mode for `panic = "abort"` builds.
+pub fn is_abort_mode() -> bool {
+    ABORT_MODE.load(Ordering::Acquire)
+}
+
+///Switches format used by installed hook to `F`.
+///
+///Takes effect immediately for all threads without re-registering hook.
+///Only has effect when hook is installed by this crate.
+pub fn switch_format<F: PanicFormat>() {
+    ACTIVE.store(Format::of::<F>() as *const Format as *mut Format, Ordering::Release);
+}
+
+///Returns number of panics handled by installed hook.
+///
+///With `metrics` feature, the same is reported as `panics_total` counter via `metrics` facade,
+///labeled by `file` and `line` of panic.
+pub fn panic_count() -> usize {
+    PANICS.load(Ordering::Acquire)
+}
+
+///Overrides panic format for the current thread.
+///
+///Only has effect when hook is installed by this crate.
+///Useful for worker threads whose panics are expected and recovered.
+pub fn set_thread_format<F: PanicFormat>() {
+    THREAD_FORMAT.with(|format| format.set(Some(Format::of::<F>())));
+}
+
+///Removes panic format override of the current thread.
+pub fn reset_thread_format() {
+    THREAD_FORMAT.with(|format| format.set(None));
+}
+
+///Runs `closure`, converting its panic into report written by components of `F`.
+///
+///Panic is not printed, while the rest of hook's handling (e.g. counting) is performed.
+///If hook is not installed by this crate, report is only panic's message.
+///Format and capture mode of the current thread are restored afterwards.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{JustError, Simple};
+///
+///fn main() {
+///    set_panic_message!(Simple);
+///
+///    let result = lazy_panic::catch::<JustError, _, _>(|| panic!("lolka"));
+///    assert_eq!(result.unwrap_err(), "lolka\n");
+///}
+///```
+pub fn catch<F: PanicFormat, R, C: FnOnce() -> R + panic::UnwindSafe>(closure: C) -> Result<R, String> {
+    let format = THREAD_FORMAT.with(|format| format.replace(Some(Format::of::<F>())));
+    let capture = CAPTURE.with(|capture| capture.replace(true));
+    LAST_REPORT.with(|last| last.borrow_mut().take());
+
+    let result = panic::catch_unwind(closure);
+
+    THREAD_FORMAT.with(|thread_format| thread_format.set(format));
+    CAPTURE.with(|thread_capture| thread_capture.set(capture));
+
+    result.map_err(|payload| match LAST_REPORT.with(|last| last.borrow_mut().take()) {
+        Some(report) => report,
+        None => payload::to_string(&*payload),
+    })
+}
+
+///Structured data of panic, as returned by [run](fn.run.html)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PanicReport {
+    ///Message of payload.
+    pub message: String,
+    ///File, line and column, with file stripped according to [location](../location/index.html) configuration.
+    pub location: Option<(String, u32, u32)>,
+    ///Name of panicked thread, if it has one.
+    pub thread: Option<String>,
+    ///Backtrace, as written by [Debug](../formatter/struct.Debug.html), if `backtrace-on` is enabled.
+    pub backtrace: Option<String>,
+}
+
+impl PanicReport {
+    fn new(info: &PanicHookInfo) -> Self {
+        let mut backtrace = Vec::new();
+        let _ = Debug::write_untranslated(&mut backtrace);
+
+        PanicReport {
+            message: payload::to_string(info.payload()),
+            location: info.location().map(|location| (location::strip(location.file()).to_owned(), location.line(), location.column())),
+            thread: thread::current().name().map(ToOwned::to_owned),
+            backtrace: match backtrace.is_empty() {
+                true => None,
+                false => Some(String::from_utf8_lossy(&backtrace).into_owned()),
+            },
+        }
+    }
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some((ref file, line, column)) => write!(fmt, "{}:{}:{} - {}", file, line, column, self.message),
+            None => write!(fmt, "unknown:0:0 - {}", self.message),
+        }
+    }
+}
+
+///Runs `closure`, converting its panic into [PanicReport](struct.PanicReport.html)
+///
+///Panic is not printed, while the rest of hook's handling (e.g. counting) is performed.
+///If hook is not installed by this crate, report only has panic's message and thread.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::Simple;
+///
+///fn main() {
+///    set_panic_message!(Simple);
+///
+///    let report = lazy_panic::run(|| panic!("lolka")).unwrap_err();
+///    assert_eq!(report.message, "lolka");
+///    assert!(report.location.is_some());
+///}
+///```
+pub fn run<T, C: FnOnce() -> T + panic::UnwindSafe>(closure: C) -> Result<T, PanicReport> {
+    let collect = COLLECT.with(|collect| collect.replace(true));
+    LAST_PANIC.with(|last| last.borrow_mut().take());
+
+    let result = panic::catch_unwind(closure);
+
+    COLLECT.with(|thread_collect| thread_collect.set(collect));
+
+    result.map_err(|payload| match LAST_PANIC.with(|last| last.borrow_mut().take()) {
+        Some(report) => report,
+        None => PanicReport {
+            message: payload::to_string(&*payload),
+            location: None,
+            thread: thread::current().name().map(ToOwned::to_owned),
+            backtrace: None,
+        },
+    })
+}
+
+fn dispatch(info: &PanicHookInfo) {
+    //Panic within format is reported by panicking thread, once it is caught.
+    if IN_HOOK.try_with(|in_hook| in_hook.get()).unwrap_or(false) {
+        return;
+    }
+
+    PANICS.fetch_add(1, Ordering::AcqRel);
+    #[cfg(feature = "metrics")]
+    count_metric(info);
+
+    //Panic of helper thread would abort process all the same.
+    if is_abort_mode() {
+        print(info);
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+        return;
+    }
+
+    if let Err(nested) = print_isolated(info) {
+        write_raw(info, &*nested);
+    }
+}
+
+//Request of helper thread to panicking one.
+enum Message {
+    //Helper is able to lock standard streams.
+    Ready,
+    //Call must be made on panicking thread, which is notified back once done.
+    Call(PrintFn, mpsc::Sender<()>),
+}
+
+//State of hook, specific to panicking thread.
+struct HookState {
+    format: Option<&'static Format>,
+    capture: bool,
+    collect: bool,
+}
+
+//Pointer to info, which is valid as long as panicking thread waits for helper.
+struct InfoPtr(*const PanicHookInfo<'static>);
+
+//Panicking thread is blocked while helper uses info, so it is never accessed concurrently.
+unsafe impl Send for InfoPtr {}
+
+const PENDING: u8 = 0;
+const READY: u8 = 1;
+const ABANDONED: u8 = 2;
+
+//Standard streams might be locked by panicking thread (e.g. panic within `Display` of `eprintln!` argument).
+//Then helper would wait for them forever, so panicking thread gives up on it.
+const READY_TIMEOUT: Duration = Duration::from_millis(100);
+
+//Std aborts right away on panic within hook, so format runs on helper thread, where panic is caught,
+//while helper acts on behalf of panicking thread.
+//Falls back to printing on panicking thread, if helper cannot be used.
+fn print_isolated(info: &PanicHookInfo) -> thread::Result<()> {
+    let state = HookState {
+        format: THREAD_FORMAT.try_with(|format| format.get()).unwrap_or(None),
+        capture: CAPTURE.try_with(|capture| capture.get()).unwrap_or(false),
+        collect: COLLECT.try_with(|collect| collect.get()).unwrap_or(false),
+    };
+    let origin = Origin::capture();
+    let status = Arc::new(AtomicU8::new(PENDING));
+    let (sender, receiver) = mpsc::channel();
+
+    let mut helper = thread::Builder::new();
+    if let Some(name) = thread::current().name() {
+        helper = helper.name(name.to_owned());
+    }
+
+    let helper_info = InfoPtr(info as *const PanicHookInfo as *const PanicHookInfo<'static>);
+    let helper_status = status.clone();
+    let helper = helper.spawn(move || {
+        drop(io::stderr().lock());
+        drop(io::stdout().lock());
+        if helper_status.compare_exchange(PENDING, READY, Ordering::AcqRel, Ordering::Acquire).is_err() {
+            return (Ok(()), None, None);
+        }
+        let _ = sender.send(Message::Ready);
+
+        let _origin = origin.enter();
+        IN_HOOK.with(|in_hook| in_hook.set(true));
+        THREAD_FORMAT.with(|format| format.set(state.format));
+        CAPTURE.with(|capture| capture.set(state.capture));
+        COLLECT.with(|collect| collect.set(state.collect));
+        CALLS.with(|calls| *calls.borrow_mut() = Some(sender));
+
+        let info = unsafe { &*helper_info.0 };
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| print(info)));
+
+        CALLS.with(|calls| calls.borrow_mut().take());
+        (result, LAST_REPORT.with(|last| last.borrow_mut().take()), LAST_PANIC.with(|last| last.borrow_mut().take()))
+    });
+
+    let helper = match helper {
+        Ok(helper) => helper,
+        Err(_) => {
+            print(info);
+            return Ok(());
+        },
+    };
+
+    match receiver.recv_timeout(READY_TIMEOUT) {
+        Ok(_) => (),
+        Err(mpsc::RecvTimeoutError::Timeout) if status.compare_exchange(PENDING, ABANDONED, Ordering::AcqRel, Ordering::Acquire).is_ok() => {
+            //Helper exits without touching info, once streams are unlocked.
+            print(info);
+            return Ok(());
+        },
+        Err(mpsc::RecvTimeoutError::Timeout) => (),
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            print(info);
+            return Ok(());
+        },
+    }
+
+    for message in receiver {
+        if let Message::Call(call, done) = message {
+            call(info);
+            let _ = done.send(());
+        }
+    }
+
+    let (result, report, panic) = helper.join()?;
+    if let Some(report) = report {
+        let _ = LAST_REPORT.try_with(|last| *last.borrow_mut() = Some(report));
+    }
+    if let Some(panic) = panic {
+        let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some(panic));
+    }
+    result
+}
+
+//Makes `call` on panicking thread, if invoked on behalf of it.
+fn call_on_origin(call: PrintFn) -> bool {
+    let sender = CALLS.try_with(|calls| calls.borrow().clone()).ok().and_then(|sender| sender);
+    match sender {
+        Some(sender) => {
+            let (done, wait) = mpsc::channel();
+            sender.send(Message::Call(call, done)).is_ok() && wait.recv().is_ok()
+        },
+        None => false,
+    }
+}
+
+//Avoids anything that can be customized by user, as it already failed once.
+fn write_raw(info: &PanicHookInfo, nested: &(dyn any::Any + Send)) {
+    fn message(payload: &(dyn any::Any + Send)) -> &str {
+        match payload.downcast_ref::<&str>() {
+            Some(payload) => payload,
+            None => match payload.downcast_ref::<String>() {
+                Some(payload) => payload.as_str(),
+                None => "Box<dyn Any>",
+            },
+        }
+    }
+
+    let stderr = io::stderr();
+    let mut stderr = stderr.lock();
+    let _ = writeln!(stderr, "panic while formatting panic: {} / {}", message(info.payload()), message(nested));
+}
+
+fn print(info: &PanicHookInfo) {
+    if COLLECT.try_with(|collect| collect.get()).unwrap_or(false) {
+        let report = PanicReport::new(info);
+        let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some(report));
+        return;
+    }
+
+    let format = match format() {
+        Some(format) => format,
+        None => return,
+    };
+
+    if CAPTURE.try_with(|capture| capture.get()).unwrap_or(false) {
+        let report = (format.report)(info);
+        let _ = LAST_REPORT.try_with(|last| *last.borrow_mut() = Some(report));
+        return;
+    }
+
+    (format.print)(info);
+}
+
+//Writer might be missing, if hook of other format has been installed since.
+fn print_preopened<F: PanicFormat>(info: &PanicHookInfo) {
+    if let Some(writer) = lock(&WRITER).as_mut() {
+        F::print_to(info, writer);
+    }
+}
+
+fn dynamic_name() -> &'static str {
+    match DYNAMIC.read().unwrap_or_else(|error| error.into_inner()).as_ref() {
+        Some((name, _)) => name,
+        None => "",
+    }
+}
+
+fn print_dynamic(info: &PanicHookInfo) {
+    if let Some((_, format)) = DYNAMIC.read().unwrap_or_else(|error| error.into_inner()).as_ref() {
+        format.print_dyn(info);
+    }
+}
+
+fn report_dynamic(info: &PanicHookInfo) -> String {
+    match DYNAMIC.read().unwrap_or_else(|error| error.into_inner()).as_ref() {
+        Some((_, format)) => format.format_dyn(info),
+        None => payload::to_string(info.payload()),
+    }
+}
+
+//Counter is labeled by location, which is bounded by code base.
+#[cfg(feature = "metrics")]
+fn count_metric(info: &PanicHookInfo) {
+    use location;
+
+    let (file, line) = match info.location() {
+        Some(location) => (location::strip(location.file()).to_owned(), location.line()),
+        None => ("unknown".to_owned(), 0),
+    };
+    metrics::counter!("panics_total", "file" => file, "line" => line.to_string()).increment(1);
+}
+
+//Format of the current thread, if any, or the active one.
+fn format() -> Option<&'static Format> {
+    //Thread local might be already destroyed if we panic during thread's teardown.
+    THREAD_FORMAT.try_with(|format| format.get()).ok().and_then(|format| format).or_else(active)
+}
+
+#[inline]
+fn active() -> Option<&'static Format> {
+    //Always points to promoted static `Format`, if set.
+    unsafe { ACTIVE.load(Ordering::Acquire).as_ref() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process;
+    use std::thread;
+
+    use formatter::{Abort, Debug, Empty, JustError, Simple};
+    use super::{install, is_installed, installed_format_name, switch_format, set_thread_format, set_abort_mode, catch, run};
+
+    const ABORT_CHILD: &str = "LAZY_PANIC_TEST_ABORT_CHILD";
+    const NESTED_CHILD: &str = "LAZY_PANIC_TEST_NESTED_CHILD";
+
+    struct Panicking;
+
+    impl ::formatter::Prefix for Panicking {
+        fn write_in<W: ::std::io::Write>(_: &mut W) -> ::std::io::Result<()> {
+            panic!("nested lolka");
+        }
+    }
+
+    type NestedFormat = ::formatter::Composite<Panicking, Simple, Simple, Empty, ::formatter::StderrWriter>;
+
+    #[test]
+    #[ignore]
+    fn nested_child() {
+        match env::var(NESTED_CHILD).as_ref().map(String::as_str) {
+            Ok("nested") => {
+                install::<NestedFormat>();
+                panic!("lolka");
+            },
+            Ok("locked") => {
+                install::<JustError>();
+                let _stderr = ::std::io::stderr().lock();
+                panic!("lolka");
+            },
+            _ => (),
+        }
+    }
+
+    fn run_nested_child(mode: &str) -> (process::ExitStatus, String) {
+        let output = process::Command::new(env::current_exe().expect("To get test executable"))
+                                      .args(["hook::tests::nested_child", "--exact", "--ignored", "--nocapture", "--test-threads=1"])
+                                      .env(NESTED_CHILD, mode)
+                                      .output()
+                                      .expect("To run child");
+
+        (output.status, String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+
+    #[test]
+    fn should_fall_back_on_panic_within_format() {
+        let (status, stderr) = run_nested_child("nested");
+        assert_eq!(status.code(), Some(101), "{}", stderr);
+        assert_eq!(stderr, "panic while formatting panic: lolka / nested lolka\n");
+
+        //Helper cannot write while stderr is locked by panicking thread.
+        let (status, stderr) = run_nested_child("locked");
+        assert_eq!(status.code(), Some(101), "{}", stderr);
+        assert_eq!(stderr, "lolka\n");
+    }
+
+    #[test]
+    #[ignore]
+    fn abort_child() {
+        if env::var_os(ABORT_CHILD).is_none() {
+            return;
+        }
+
+        set_abort_mode(true);
+        install::<Abort<Simple>>();
+        panic!("lolka");
+    }
+
+    #[test]
+    fn should_write_report_before_abort() {
+        let output = process::Command::new(env::current_exe().expect("To get test executable"))
+                                      .args(["hook::tests::abort_child", "--exact", "--ignored", "--nocapture", "--test-threads=1"])
+                                      .env(ABORT_CHILD, "1")
+                                      .output()
+                                      .expect("To run child");
+
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("Panic: "), "{}", stderr);
+        assert!(stderr.contains("lolka\n"), "{}", stderr);
+    }
+
+    #[test]
+    fn should_use_thread_format() {
+        install::<Debug>();
+
+        let worker = thread::spawn(|| {
+            set_thread_format::<Empty>();
+            panic!("lolka");
+        });
+
+        assert!(worker.join().is_err());
+    }
+
+    #[test]
+    fn should_report_installed_format() {
+        install::<Debug>();
+
+        assert!(is_installed());
+        assert!(installed_format_name().is_some());
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_switch_format() {
+        install::<Debug>();
+        switch_format::<JustError>();
+        panic!("lolka");
+    }
+
+    #[test]
+    fn should_catch_panic() {
+        install::<Debug>();
+
+        assert_eq!(catch::<JustError, _, _>(|| 1), Ok(1));
+        let report = catch::<JustError, _, _>(|| panic!("lolka")).unwrap_err();
+        assert!(report.starts_with("lolka"), "{}", report);
+    }
+
+    #[test]
+    fn should_run_to_report() {
+        install::<Debug>();
+
+        assert_eq!(run(|| 1), Ok(1));
+
+        let line = line!() + 1;
+        let report = thread::Builder::new().name("worker".to_owned()).spawn(move || run(|| panic!("lolka {}", 1)).unwrap_err()).unwrap().join().unwrap();
+        assert_eq!(report.message, "lolka 1");
+        let (file, report_line, column) = report.location.clone().expect("To have location");
+        assert_eq!((file.as_str(), report_line), ("src/hook.rs", line));
+        assert_eq!(report.thread.as_deref(), Some("worker"));
+        assert_eq!(report.backtrace.is_some(), cfg!(feature = "backtrace-on"));
+        assert_eq!(report.to_string(), format!("src/hook.rs:{}:{} - lolka 1", line, column));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn should_count_panic_metric() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        use metrics;
+
+        struct Recorder(Arc<AtomicU64>);
+
+        impl metrics::Recorder for Recorder {
+            fn describe_counter(&self, _: metrics::KeyName, _: Option<metrics::Unit>, _: metrics::SharedString) {
+            }
+            fn describe_gauge(&self, _: metrics::KeyName, _: Option<metrics::Unit>, _: metrics::SharedString) {
+            }
+            fn describe_histogram(&self, _: metrics::KeyName, _: Option<metrics::Unit>, _: metrics::SharedString) {
+            }
+
+            fn register_counter(&self, key: &metrics::Key, _: &metrics::Metadata) -> metrics::Counter {
+                assert_eq!(key.name(), "panics_total");
+                assert!(key.labels().any(|label| label.key() == "file" && label.value() == "src/hook.rs"));
+                metrics::Counter::from_arc(self.0.clone())
+            }
+
+            fn register_gauge(&self, _: &metrics::Key, _: &metrics::Metadata) -> metrics::Gauge {
+                metrics::Gauge::noop()
+            }
+
+            fn register_histogram(&self, _: &metrics::Key, _: &metrics::Metadata) -> metrics::Histogram {
+                metrics::Histogram::noop()
+            }
+        }
+
+        install::<Debug>();
+        let count = Arc::new(AtomicU64::new(0));
+        let recorder = Recorder(count.clone());
+
+        let worker = thread::spawn(move || {
+            set_thread_format::<Empty>();
+            metrics::with_local_recorder(&recorder, || panic!("lolka"));
+        });
+        assert!(worker.join().is_err());
+        assert_eq!(count.load(Ordering::Acquire), 1);
+    }
+}
@@ -0,0 +1,442 @@
+//! Capture of panic reports for test assertions.
+//!
+//!In capture mode panics of the current thread are not printed.
+//!Instead report, as written by components of format in use, is stored
+//!to be retrieved via [last_report](fn.last_report.html).
+//!
+//!Note that `PanicFormat::print` is not invoked in capture mode,
+//!so formats that override it (e.g. [Abort](../../formatter/struct.Abort.html)) only have their components written.
+//!
+//!```rust
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use std::panic;
+//!
+//!use lazy_panic::formatter::JustError;
+//!
+//!fn main() {
+//!    set_panic_message!(JustError);
+//!    lazy_panic::test::set_capture(true);
+//!
+//!    assert!(panic::catch_unwind(|| panic!("lolka")).is_err());
+//!    assert_eq!(lazy_panic::test::last_report().unwrap(), "lolka\n");
+//!}
+//!```
+
+use std::any::Any;
+use std::cell::Cell;
+use std::io;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use formatter::{Empty, PanicFormat, Section, Simple};
+use lock;
+use origin;
+use PanicHookInfo;
+use super::{call_on_origin, catch, format, install, is_installed, Hook, CAPTURE, LAST_REPORT};
+
+//Callback of `with_panic_info`, alive while it waits for panic to be formatted.
+type InfoFn = *mut (dyn FnMut(&PanicHookInfo) + 'static);
+
+thread_local!(static WITH_INFO: Cell<Option<InfoFn>> = const { Cell::new(None) });
+
+///Sets whether panics of the current thread are captured instead of being printed.
+///
+///Only has effect when hook is installed by this crate.
+pub fn set_capture(enabled: bool) {
+    CAPTURE.with(|capture| capture.set(enabled));
+}
+
+///Returns whether panics of the current thread are captured.
+pub fn is_capture() -> bool {
+    CAPTURE.with(|capture| capture.get())
+}
+
+///Returns report of the last panic captured on the current thread.
+pub fn last_report() -> Option<String> {
+    LAST_REPORT.with(|last| last.borrow().clone())
+}
+
+///Runs `closure` and returns report of its panic, as written by components of `F`.
+///
+///Returns `None` if `closure` doesn't panic.
+///Installs hook with [Simple](../../formatter/struct.Simple.html) first, if hook is not installed by this crate.
+///See [catch](../fn.catch.html) for details.
+pub fn capture_report<F: PanicFormat, R, C: FnOnce() -> R + panic::UnwindSafe>(closure: C) -> Option<String> {
+    if !is_installed() {
+        install::<Simple>();
+    }
+
+    let report = catch::<F, _, _>(closure).err();
+    if let Some(ref report) = report {
+        LAST_REPORT.with(|last| *last.borrow_mut() = Some(report.clone()));
+    }
+    report
+}
+
+///Formats panic with `payload` by components of `F` into `String`.
+///
+///`PanicInfo` cannot be constructed directly, so actual panic is raised via `panic::panic_any`
+///and captured as by [capture_report](fn.capture_report.html).
+///Its location is always within this function, so output is deterministic.
+///For panic at specific location use `capture_report` with closure that panics there.
+///
+///```rust
+///use lazy_panic::formatter::JustError;
+///use lazy_panic::test::format_panic;
+///
+///assert_eq!(format_panic::<JustError, _>("lolka"), "lolka\n");
+///assert_eq!(format_panic::<JustError, _>(1u8), "Any { .. }\n");
+///```
+pub fn format_panic<F: PanicFormat, P: Any + Send + 'static>(payload: P) -> String {
+    capture_report::<F, _, _>(panic::AssertUnwindSafe(move || panic::panic_any(payload))).unwrap_or_default()
+}
+
+///Calls `f` with info of panic with `payload` and returns its result.
+///
+///Panic is raised and captured as by [format_panic](fn.format_panic.html), so its location is within this function.
+///`f` is called on the current thread.
+///
+///```rust
+///use lazy_panic::payload;
+///use lazy_panic::test::with_panic_info;
+///
+///assert_eq!(with_panic_info("lolka", |info| payload::to_string(info.payload())), "lolka");
+///```
+pub fn with_panic_info<P: Any + Send + 'static, R, F: FnOnce(&PanicHookInfo) -> R>(payload: P, f: F) -> R {
+    let mut f = Some(f);
+    let mut result = None;
+
+    {
+        let mut call = |info: &PanicHookInfo| if let Some(f) = f.take() {
+            result = Some(f(info));
+        };
+        let call: &mut dyn FnMut(&PanicHookInfo) = &mut call;
+        //Pointer is only used until panic is captured, while `call` is alive.
+        let call: InfoFn = unsafe { mem::transmute(call) };
+
+        let previous = WITH_INFO.with(|with| with.replace(Some(call)));
+        capture_report::<WithInfo, _, _>(panic::AssertUnwindSafe(move || panic::panic_any(payload)));
+        WITH_INFO.with(|with| with.set(previous));
+    }
+
+    result.expect("To call with panic info")
+}
+
+//Calls callback of `with_panic_info` instead of writing report.
+struct WithInfo;
+
+impl PanicFormat for WithInfo {
+    type Writer = io::Sink;
+    type Backtrace = Empty;
+    type Prefix = Empty;
+    type PanicInfo = Empty;
+    type Suffix = Empty;
+
+    fn writer() -> Self::Writer {
+        io::sink()
+    }
+
+    fn format_to_string(info: &PanicHookInfo) -> String {
+        //Callback is only available on the panicking thread.
+        if !call_on_origin(call_with_info) {
+            call_with_info(info);
+        }
+        String::new()
+    }
+}
+
+fn call_with_info(info: &PanicHookInfo) {
+    if let Some(call) = WITH_INFO.with(|with| with.get()) {
+        unsafe { (*call)(info) }
+    }
+}
+
+///Captures reports of all panics, including ones of spawned threads, until dropped.
+///
+///Swaps in capturing hook, which writes report by format of the panicking thread
+///or the one of installed hook, falling back to [Simple](../../formatter/struct.Simple.html).
+///Previous hook is restored on drop.
+///
+///Note that hook is process-wide, so panics of concurrently running tests are captured too.
+///
+///```rust
+///use std::thread;
+///
+///use lazy_panic::test::PanicCapture;
+///
+///let capture = PanicCapture::new();
+///assert!(thread::spawn(|| panic!("lolka")).join().is_err());
+///assert!(capture.reports()[0].ends_with("lolka\n"));
+///```
+pub struct PanicCapture {
+    reports: Arc<Mutex<Vec<String>>>,
+    previous: Option<Hook>,
+}
+
+impl PanicCapture {
+    ///Starts capturing panics.
+    pub fn new() -> Self {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let hook_reports = reports.clone();
+
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let report = match format() {
+                Some(format) => (format.report)(info),
+                None => Simple::format_to_string(info),
+            };
+            lock(&hook_reports).push(report);
+        }));
+
+        PanicCapture {
+            reports,
+            previous: Some(previous),
+        }
+    }
+
+    ///Returns reports of panics captured so far.
+    pub fn reports(&self) -> Vec<String> {
+        lock(&self.reports).clone()
+    }
+}
+
+impl Default for PanicCapture {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for PanicCapture {
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous.take() {
+            panic::set_hook(previous);
+        }
+    }
+}
+
+static CAPTURED: Mutex<Vec<(String, Vec<u8>)>> = Mutex::new(Vec::new());
+
+//Test harness names thread of each test after it.
+fn thread_key() -> String {
+    let thread = thread::current();
+    match thread.name() {
+        Some(name) => name.to_owned(),
+        //Panic is written on helper thread, acting on behalf of panicking one.
+        None => origin::thread_id(),
+    }
+}
+
+///Writer that stores output per thread on flush, keyed by thread's name or id.
+///
+///As test harness runs each test in thread named after it, tests running in parallel
+///don't clobber each other's output.
+///Output is retrieved via [captured](fn.captured.html) and [captured_by](fn.captured_by.html)
+#[derive(Default)]
+pub struct CaptureWriter {
+    buffer: Vec<u8>,
+}
+
+impl CaptureWriter {
+    ///Creates new instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl io::Write for CaptureWriter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let key = thread_key();
+        let mut captured = lock(&CAPTURED);
+        match captured.iter_mut().find(|entry| entry.0 == key) {
+            Some(entry) => entry.1.append(&mut self.buffer),
+            None => captured.push((key, self.buffer.split_off(0))),
+        }
+
+        Ok(())
+    }
+}
+
+///Writes components of `F` into [CaptureWriter](struct.CaptureWriter.html)
+///
+///```rust
+///use std::thread;
+///
+///use lazy_panic::formatter::JustError;
+///use lazy_panic::test::{self, Captured};
+///
+///lazy_panic::hook::install::<JustError>();
+///let worker = thread::Builder::new().name("worker".to_owned()).spawn(|| {
+///    lazy_panic::set_thread_format::<Captured<JustError>>();
+///    panic!("lolka");
+///}).unwrap();
+///
+///assert!(worker.join().is_err());
+///assert_eq!(test::captured_by("worker"), "lolka\n");
+///```
+pub struct Captured<F> {
+    _format: PhantomData<F>,
+}
+
+impl<F: PanicFormat> PanicFormat for Captured<F> {
+    type Writer = CaptureWriter;
+    type Backtrace = F::Backtrace;
+    type Prefix = F::Prefix;
+    type PanicInfo = F::PanicInfo;
+    type Suffix = F::Suffix;
+
+    const TWO_STAGE: bool = F::TWO_STAGE;
+    const SECTIONS: &'static [Section] = F::SECTIONS;
+
+    #[inline]
+    fn writer() -> Self::Writer {
+        CaptureWriter::new()
+    }
+}
+
+///Returns output captured for the current thread.
+pub fn captured() -> String {
+    captured_by(&thread_key())
+}
+
+///Returns output captured for thread with specified name.
+pub fn captured_by(thread: &str) -> String {
+    match lock(&CAPTURED).iter().find(|entry| entry.0 == thread) {
+        Some(entry) => String::from_utf8_lossy(&entry.1).into_owned(),
+        None => String::new(),
+    }
+}
+
+///Removes output captured for the current thread.
+pub fn clear_captured() {
+    let key = thread_key();
+    lock(&CAPTURED).retain(|entry| entry.0 != key);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::panic;
+    use std::process;
+    use std::thread;
+
+    use formatter::{JustError, Simple};
+    use hook::{install, set_thread_format};
+    use super::{set_capture, is_capture, last_report, capture_report, format_panic, PanicCapture};
+    use super::{captured, captured_by, clear_captured, Captured, CaptureWriter};
+
+    const CAPTURE_CHILD: &str = "LAZY_PANIC_TEST_CAPTURE_CHILD";
+
+    #[test]
+    fn should_capture_report() {
+        install::<Simple>();
+        set_thread_format::<Simple>();
+        set_capture(true);
+        assert!(is_capture());
+
+        let line = line!() + 1;
+        assert!(panic::catch_unwind(|| panic!("lolka")).is_err());
+
+        let report = last_report().expect("To capture report");
+        assert!(report.starts_with(&format!("Panic: src/hook/test.rs:{}:", line)), "{}", report);
+        assert!(report.contains(" - lolka"), "{}", report);
+        assert!(report.ends_with('\n'), "{}", report);
+    }
+    #[test]
+    fn should_capture_report_of_closure() {
+        let report = capture_report::<JustError, _, _>(|| panic!("lolka")).expect("To capture report");
+        assert!(report.starts_with("lolka"), "{}", report);
+        assert_eq!(capture_report::<JustError, _, _>(|| 1), None);
+        assert!(!is_capture());
+
+        assert_panic_message!(|| panic!("lolka {}", 1), "lolka 1");
+        assert_panic_message!(|| panic!("lolka"), "Panic: src/hook/test.rs:", Simple);
+    }
+
+    #[test]
+    #[should_panic(expected = "didn't panic")]
+    fn should_fail_assert_without_panic() {
+        assert_panic_message!(|| 1, "lolka");
+    }
+
+    #[test]
+    fn should_format_panic() {
+        let report = format_panic::<Simple, _>("lolka".to_owned());
+        assert!(report.starts_with("Panic: src/hook/test.rs:"), "{}", report);
+        assert!(report.contains(" - lolka"), "{}", report);
+    }
+
+    #[test]
+    fn should_capture_output_per_thread() {
+        use std::io::Write;
+
+        install::<Simple>();
+
+        let workers: Vec<_> = (0..4).map(|idx| {
+            thread::Builder::new().name(format!("capture-worker-{}", idx)).spawn(move || {
+                set_thread_format::<Captured<JustError>>();
+                panic!("lolka {}", idx);
+            }).unwrap()
+        }).collect();
+        for worker in workers {
+            assert!(worker.join().is_err());
+        }
+
+        for idx in 0..4 {
+            let output = captured_by(&format!("capture-worker-{}", idx));
+            assert!(output.starts_with(&format!("lolka {}", idx)), "{}", output);
+        }
+
+        let mut writer = CaptureWriter::new();
+        write!(writer, "lolka").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(captured(), "lolka");
+        clear_captured();
+        assert_eq!(captured(), "");
+    }
+
+    //Swaps process-wide hook, so it runs in own process to not capture panics of other tests.
+    #[test]
+    #[ignore]
+    fn capture_child() {
+        if env::var_os(CAPTURE_CHILD).is_none() {
+            return;
+        }
+
+        install::<JustError>();
+        let capture = PanicCapture::new();
+        assert!(thread::spawn(|| panic!("lolka")).join().is_err());
+        assert!(panic::catch_unwind(|| panic!("lolka 2")).is_err());
+        assert_eq!(capture.reports(), ["lolka\n", "lolka 2\n"]);
+
+        drop(capture);
+        assert_eq!(capture_report::<JustError, _, _>(|| panic!("lolka 3")).as_deref(), Some("lolka 3\n"));
+    }
+
+    #[test]
+    fn should_capture_panics_within_scope() {
+        let output = process::Command::new(env::current_exe().expect("To get test executable"))
+                                      .args(["hook::test::tests::capture_child", "--exact", "--ignored", "--test-threads=1"])
+                                      .env(CAPTURE_CHILD, "1")
+                                      .output()
+                                      .expect("To run child");
+
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("1 passed"));
+    }
+}
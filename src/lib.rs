@@ -1,18 +1,52 @@
 //! Provides lazy utilities to lazily set custom panic hook.
 
+#![cfg_attr(not(feature = "std"), no_std)]
+
 ///Formats ```PanicInfo``` payload into ```String```
 ///
 ///# Arguments
 ///
 ///* ```payload``` - ```PanicInfo``` payload message.
 ///* ```p_type``` - Multiple number of types which payload can be. If not among these types then it
-///is formatted as ```{:?}```
+///  is formatted as ```{:?}```
+///* ```fallback``` - Optional closure-like expression ```|writer, payload| ...``` that handles payload
+///  which is not among ```p_type```, instead of ```{:?}```. Writer is passed as it is.
 ///
 ///# Return
 ///
 ///Result of `write!` macro
+///
+///# Example
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use std::io::Write;
+///
+///fn main() {
+///    let payload: Box<dyn std::any::Any + Send> = Box::new(1u8);
+///    let mut buffer = Vec::new();
+///
+///    write_payload!(&mut buffer, &*payload, types: [&str, String], fallback: |writer, payload| {
+///        write!(writer, "<unknown payload {:?}>", payload.type_id())
+///    }).expect("To write");
+///}
+///```
 #[macro_export]
 macro_rules! write_payload {
+    ($writer:expr, $payload:expr, types: [$($p_type:ty),+], fallback: |$fallback_writer:ident, $fallback_payload:ident| $fallback:expr) => {{
+        $(
+            if let Some(result) = $payload.downcast_ref::<$p_type>() {
+                write!($writer, "{}", result)
+            }
+         )else+
+         else {
+             let $fallback_writer = $writer;
+             let $fallback_payload = $payload;
+             $fallback
+         }
+    }};
     ($writer:expr, $payload:expr, types: [$($p_type:ty),+]) => {{
         $(
             if let Some(result) = $payload.downcast_ref::<$p_type>() {
@@ -25,21 +59,421 @@ macro_rules! write_payload {
     }}
 }
 
+///Formats ```PanicInfo``` payload using default set of types.
+///
+///Besides ```&str``` and ```String```, handles ```Cow<str>```, ```Box<str>``` and types
+///registered via [payload::register](payload/fn.register.html)
+///
+///Note that ```fmt::Arguments``` is not ```Send``` and therefore cannot be panic's payload.
+///
+///# Arguments
+///
+///* ```writer``` - Mutable reference to ```io::Write```
+///* ```payload``` - ```PanicInfo``` payload message.
+///
+///# Return
+///
+///```io::Result<()>```
+#[macro_export]
+macro_rules! write_payload_default {
+    ($writer:expr, $payload:expr) => {
+        $crate::payload::write($writer, $payload)
+    }
+}
+
 ///Sets custom printer for panic.
 ///
 ///# Arguments
 ///
 ///* ```Config``` - panic formatter that implements [PanicFormat](formatter/trait.PanicFormat.html)
+///
+///# Formatter value
+///
+///Use ```dyn``` followed by expression implementing [DynPanicFormat](formatter/trait.DynPanicFormat.html)
+///to install formatter with runtime state, e.g. ```set_panic_message!(dyn Tagged::new(tag))```
+///
+///# Build dependent printer
+///
+///Use ```debug``` and ```release``` arguments to select printer by ```debug_assertions``` of
+///the calling crate:
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///fn main() {
+///    set_panic_message!(debug: lazy_panic::formatter::Debug, release: lazy_panic::formatter::JustError);
+///}
+///```
 #[macro_export]
 macro_rules! set_panic_message {
+    (debug: $debug:ty, release: $release:ty) => {{
+        if cfg!(debug_assertions) {
+            $crate::hook::install::<$debug>()
+        } else {
+            $crate::hook::install::<$release>()
+        }
+    }};
+    (dyn $format:expr) => {{
+        $crate::hook::install_dyn($format)
+    }};
     ($config:ty) => {{
-        use std::panic;
-        use $crate::formatter::PanicFormat;
-        type Printer = $config;
-        panic::set_hook(Box::new(move |info| {
-            Printer::print(&info);
-        }))
+        $crate::hook::install::<$config>()
+    }}
+}
+
+///Defines unit struct implementing [PanicFormat](formatter/trait.PanicFormat.html) from inline pieces.
+///
+///# Arguments
+///
+///* ```prefix``` - String literal or type implementing [Prefix](formatter/trait.Prefix.html)
+///* ```info``` - Type implementing [PanicInfo](formatter/trait.PanicInfo.html)
+///* ```suffix``` - String literal or type implementing [Suffix](formatter/trait.Suffix.html)
+///* ```backtrace``` - Type implementing [Backtrace](formatter/trait.Backtrace.html)
+///* ```writer``` - ```stderr```, ```stdout``` or type implementing ```io::Write``` and ```Default```
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::{Debug, Simple, Timestamp};
+///
+///define_panic_format!(Fatal { prefix: "fatal: ", info: Simple, suffix: "\n", backtrace: Debug, writer: stderr });
+///define_panic_format!(pub Logged { prefix: Timestamp, info: Simple, suffix: Simple, backtrace: Debug, writer: std::io::Sink });
+///
+///fn main() {
+///    set_panic_message!(Fatal);
+///}
+///```
+#[macro_export]
+macro_rules! define_panic_format {
+    ($(#[$attr:meta])* $vis:vis $name:ident { prefix: $prefix:literal, $($rest:tt)* }) => {
+        impl $crate::formatter::Prefix for $name {
+            #[inline]
+            fn write_in<W: ::std::io::Write>(writer: &mut W) -> ::std::io::Result<()> {
+                writer.write_all($prefix.as_bytes())
+            }
+        }
+
+        define_panic_format!(@info $name [$(#[$attr])* $vis] [$name] $($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis $name:ident { prefix: $prefix:ty, $($rest:tt)* }) => {
+        define_panic_format!(@info $name [$(#[$attr])* $vis] [$prefix] $($rest)*);
+    };
+    (@info $name:ident [$($head:tt)*] [$prefix:ty] info: $info:ty, suffix: $suffix:literal, $($rest:tt)*) => {
+        impl $crate::formatter::Suffix for $name {
+            #[inline]
+            fn write_in<W: ::std::io::Write>(writer: &mut W) -> ::std::io::Result<()> {
+                writer.write_all($suffix.as_bytes())
+            }
+        }
+
+        define_panic_format!(@backtrace $name [$($head)*] [$prefix] [$info] [$name] $($rest)*);
+    };
+    (@info $name:ident [$($head:tt)*] [$prefix:ty] info: $info:ty, suffix: $suffix:ty, $($rest:tt)*) => {
+        define_panic_format!(@backtrace $name [$($head)*] [$prefix] [$info] [$suffix] $($rest)*);
+    };
+    (@backtrace $name:ident [$($head:tt)*] [$prefix:ty] [$info:ty] [$suffix:ty] backtrace: $backtrace:ty, writer: stderr $(,)*) => {
+        define_panic_format!(@define $name [$($head)*] [$prefix] [$info] [$suffix] [$backtrace] [::std::io::BufWriter<::std::io::Stderr>] ::std::io::BufWriter::new(::std::io::stderr()));
+    };
+    (@backtrace $name:ident [$($head:tt)*] [$prefix:ty] [$info:ty] [$suffix:ty] backtrace: $backtrace:ty, writer: stdout $(,)*) => {
+        define_panic_format!(@define $name [$($head)*] [$prefix] [$info] [$suffix] [$backtrace] [::std::io::BufWriter<::std::io::Stdout>] ::std::io::BufWriter::new(::std::io::stdout()));
+    };
+    (@backtrace $name:ident [$($head:tt)*] [$prefix:ty] [$info:ty] [$suffix:ty] backtrace: $backtrace:ty, writer: $writer:ty $(,)*) => {
+        define_panic_format!(@define $name [$($head)*] [$prefix] [$info] [$suffix] [$backtrace] [$writer] ::std::default::Default::default());
+    };
+    (@define $name:ident [$($head:tt)*] [$prefix:ty] [$info:ty] [$suffix:ty] [$backtrace:ty] [$writer:ty] $new_writer:expr) => {
+        $($head)* struct $name;
+
+        impl $crate::formatter::PanicFormat for $name {
+            type Writer = $writer;
+            type Backtrace = $backtrace;
+            type Prefix = $prefix;
+            type PanicInfo = $info;
+            type Suffix = $suffix;
+
+            #[inline]
+            fn writer() -> Self::Writer {
+                $new_writer
+            }
+        }
+    };
+}
+
+///Defines unit struct implementing [Text](formatter/trait.Text.html)
+///
+///To be written via [ConstPrefix](formatter/struct.ConstPrefix.html) and [ConstSuffix](formatter/struct.ConstSuffix.html)
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///define_text!(pub Daemon = "[my-daemon] ");
+///
+///type Prefix = lazy_panic::formatter::ConstPrefix<Daemon>;
+///
+///fn main() {
+///}
+///```
+#[macro_export]
+macro_rules! define_text {
+    ($(#[$attr:meta])* $vis:vis $name:ident = $text:expr) => {
+        $(#[$attr])* $vis struct $name;
+
+        impl $crate::formatter::Text for $name {
+            const TEXT: &'static str = $text;
+        }
+    };
+}
+
+///Defines unit struct implementing [WriteFn](formatter/trait.WriteFn.html)
+///
+///Function is either path to `fn(&mut dyn io::Write) -> io::Result<()>` or closure without captures.
+///To be written via [FnPrefix](formatter/struct.FnPrefix.html) and [FnSuffix](formatter/struct.FnSuffix.html)
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use std::io;
+///
+///fn separator(writer: &mut dyn io::Write) -> io::Result<()> {
+///    writer.write_all(b"\n---\n")
+///}
+///
+///define_fn!(Separator = separator);
+///define_fn!(pub Tag = |writer| writer.write_all(b"[my-daemon] "));
+///
+///type Prefix = lazy_panic::formatter::FnPrefix<Tag>;
+///type Suffix = lazy_panic::formatter::FnSuffix<Separator>;
+///
+///fn main() {
+///}
+///```
+#[macro_export]
+macro_rules! define_fn {
+    ($(#[$attr:meta])* $vis:vis $name:ident = $write:expr) => {
+        $(#[$attr])* $vis struct $name;
+
+        impl $crate::formatter::WriteFn for $name {
+            const WRITE: fn(&mut dyn std::io::Write) -> ::std::io::Result<()> = $write;
+        }
+    };
+}
+
+///Asserts that closure panics with report containing expected text.
+///
+///Report is captured via [test::capture_report](hook/test/fn.capture_report.html) and written by
+///the format passed as last argument, [Simple](formatter/struct.Simple.html) by default.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::JustError;
+///
+///fn main() {
+///    assert_panic_message!(|| panic!("lolka {}", 1), "lolka 1");
+///    assert_panic_message!(|| panic!("lolka"), "lolka\n", JustError);
+///}
+///```
+#[macro_export]
+macro_rules! assert_panic_message {
+    ($closure:expr, $expected:expr) => {
+        assert_panic_message!($closure, $expected, $crate::formatter::Simple)
+    };
+    ($closure:expr, $expected:expr, $format:ty) => {{
+        let expected = $expected;
+        match $crate::test::capture_report::<$format, _, _>(::std::panic::AssertUnwindSafe($closure)) {
+            Some(report) => assert!(report.contains(expected), "Panic report {:?} doesn't contain {:?}", report, expected),
+            None => panic!("Closure didn't panic, expected report containing {:?}", expected),
+        }
+    }};
+}
+
+///Installs hook with specified format before ```main``` runs.
+///
+///Registers constructor, so that binaries without explicit setup (e.g. test harnesses)
+///get the hook too.
+///
+///Supported on Linux, Android, BSDs, macOS, iOS and Windows.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///use lazy_panic::formatter::JustError;
+///
+///install!(JustError);
+///
+///fn main() {
+///    assert!(lazy_panic::installed_format_name().unwrap().ends_with("JustError"));
+///}
+///```
+#[macro_export]
+macro_rules! install {
+    ($format:ty) => {
+        const _: () = {
+            extern "C" fn install() {
+                $crate::hook::install::<$format>();
+            }
+
+            #[used]
+            #[cfg_attr(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd", target_os = "dragonfly", target_os = "illumos"), link_section = ".init_array")]
+            #[cfg_attr(any(target_os = "macos", target_os = "ios"), link_section = "__DATA,__mod_init_func")]
+            #[cfg_attr(windows, link_section = ".CRT$XCU")]
+            static INSTALL: extern "C" fn() = install;
+        };
+    };
+}
+
+///Registers name and version of the calling crate as application's metadata.
+///
+///These are taken from ```CARGO_PKG_NAME``` and ```CARGO_PKG_VERSION``` at build time of the
+///calling crate and written by [AppInfo](formatter/struct.AppInfo.html)
+#[macro_export]
+macro_rules! app_info {
+    () => {{
+        $crate::metadata::set_app_info(env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))
+    }}
+}
+
+///Registers build information of the calling crate as application's metadata.
+///
+///Values are emitted by [build::emit](build/fn.emit.html) from build script of the calling crate
+///and written by [BuildInfo](formatter/struct.BuildInfo.html)
+///
+///Without build script, profile is guessed from ```debug_assertions``` and other values are
+///```unknown```
+#[macro_export]
+macro_rules! build_info {
+    () => {{
+        $crate::metadata::set_build_info(
+            option_env!("LAZY_PANIC_BUILD_PROFILE").unwrap_or(if cfg!(debug_assertions) { "debug" } else { "release" }),
+            option_env!("LAZY_PANIC_BUILD_OPT_LEVEL").unwrap_or("unknown"),
+            option_env!("LAZY_PANIC_BUILD_RUSTC").unwrap_or("unknown"),
+        )
     }}
 }
 
+///Describes what current thread is doing, until returned guard is dropped.
+///
+///Accepts the same arguments as ```format!```
+///
+///Active context is written by built-in formatters on panic.
+///
+///```rust
+///#[macro_use]
+///extern crate lazy_panic;
+///
+///fn process(order: u32) {
+///    let _context = context!("processing order {}", order);
+///}
+///
+///fn main() {
+///    process(1);
+///}
+///```
+#[macro_export]
+macro_rules! context {
+    ($($arg:tt)+) => {
+        $crate::context::push(format!($($arg)+))
+    }
+}
+
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "ureq")]
+extern crate ureq;
+#[cfg(feature = "slog")]
+extern crate slog;
+#[cfg(feature = "defmt")]
+extern crate defmt;
+#[cfg(feature = "anyhow")]
+extern crate anyhow;
+#[cfg(feature = "eyre")]
+extern crate eyre;
+#[cfg(feature = "metrics")]
+extern crate metrics;
+#[cfg(feature = "opentelemetry")]
+extern crate opentelemetry;
+#[cfg(all(feature = "minidump", any(target_os = "linux", target_os = "android", target_os = "windows", target_os = "macos")))]
+extern crate minidump_writer;
+#[cfg(all(feature = "minidump", any(target_os = "linux", target_os = "android")))]
+extern crate libc;
+#[cfg(all(feature = "wer", windows))]
+extern crate windows_sys;
+#[cfg(feature = "macros")]
+extern crate lazy_panic_macros;
+
+#[cfg(feature = "std")]
+extern crate core;
+
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+pub mod bare;
+#[cfg(feature = "std")]
 pub mod formatter;
+#[cfg(feature = "std")]
+pub mod hook;
+#[cfg(feature = "std")]
+pub mod metadata;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod location;
+#[cfg(feature = "std")]
+pub mod build;
+#[cfg(feature = "std")]
+pub mod fingerprint;
+#[cfg(feature = "std")]
+pub mod runbook;
+#[cfg(feature = "std")]
+pub mod payload;
+#[cfg(feature = "std")]
+pub mod exit_code;
+#[cfg(feature = "std")]
+pub mod notify;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod locale;
+#[cfg(feature = "std")]
+mod origin;
+
+#[cfg(feature = "std")]
+pub use hook::{is_installed, installed_at, installed_format_name, panic_count};
+#[cfg(feature = "std")]
+pub use hook::{switch_format, set_thread_format, reset_thread_format, catch, run, PanicReport};
+#[cfg(feature = "std")]
+pub use hook::test;
+#[cfg(feature = "std")]
+pub use payload::to_string as payload_to_string;
+#[cfg(feature = "std")]
+pub use formatter::DisplayPanic;
+#[cfg(feature = "std")]
+pub use config::PanicConfig;
+
+///Information passed to panic hook.
+///
+///`std::panic::PanicHookInfo` since Rust 1.81, `std::panic::PanicInfo` before it.
+#[cfg(all(feature = "std", panic_hook_info))]
+#[allow(clippy::incompatible_msrv)]
+pub type PanicHookInfo<'a> = std::panic::PanicHookInfo<'a>;
+///Information passed to panic hook.
+///
+///`std::panic::PanicHookInfo` since Rust 1.81, `std::panic::PanicInfo` before it.
+#[cfg(all(feature = "std", not(panic_hook_info)))]
+pub type PanicHookInfo<'a> = std::panic::PanicInfo<'a>;
+#[cfg(feature = "macros")]
+pub use lazy_panic_macros::main;
+
+//Panic report should be written even if some other thread panicked while holding lock.
+#[cfg(feature = "std")]
+#[inline]
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
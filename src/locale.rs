@@ -0,0 +1,177 @@
+//! Translation of fixed parts of output.
+//!
+//!Table of translations is registered at install time, so that crash is reported in user's language.
+//!Only fixed text is translated, while panic's message and metadata are written as they are.
+//!
+//!```rust
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::locale::{self, Key};
+//!use lazy_panic::formatter::Debug;
+//!
+//!fn main() {
+//!    locale::register(&[
+//!        (Key::Panic, "Panik"),
+//!        (Key::StackBacktrace, "Aufrufliste"),
+//!    ]);
+//!    set_panic_message!(Debug);
+//!}
+//!```
+
+use std::borrow::Cow;
+use std::sync::RwLock;
+
+///Fixed text of output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    ///`Panic`, followed by `: ` in [Simple](../formatter/struct.Simple.html)
+    Panic,
+    ///`Stack backtrace`, followed by `:` in [Debug](../formatter/struct.Debug.html)
+    StackBacktrace,
+    ///Header of [Pretty](../formatter/struct.Pretty.html)
+    Crashed,
+    ///Label of message in [Pretty](../formatter/struct.Pretty.html)
+    Message,
+    ///Label of location in [Pretty](../formatter/struct.Pretty.html)
+    Location,
+    ///Title of metadata section in [Pretty](../formatter/struct.Pretty.html)
+    Metadata,
+    ///Title of context section in [Pretty](../formatter/struct.Pretty.html)
+    Context,
+    ///Title of backtrace section in [Pretty](../formatter/struct.Pretty.html)
+    Backtrace,
+    ///Notice of [Pretty](../formatter/struct.Pretty.html) when backtrace is not requested via `RUST_BACKTRACE`
+    BacktraceNotRequested,
+    ///Notice of [Pretty](../formatter/struct.Pretty.html) when `backtrace-on` is disabled.
+    BacktraceDisabled,
+    ///Footer of [Pretty](../formatter/struct.Pretty.html)
+    ReportIssue,
+    ///Opening of [ReportFile](../notify/report/struct.ReportFile.html) notice.
+    Embarrassing,
+    ///Notice of [ReportFile](../notify/report/struct.ReportFile.html) with `{app}` and `{path}` placeholders.
+    ReportWritten,
+    ///Default instructions of [ReportFile](../notify/report/struct.ReportFile.html)
+    ReportInstructions,
+    ///Error of [ReportFile](../notify/report/struct.ReportFile.html) with `{path}` and `{error}` placeholders.
+    ReportFailed,
+}
+
+impl Key {
+    ///Returns English text, used when there is no translation.
+    pub fn default_text(self) -> &'static str {
+        match self {
+            Key::Panic => "Panic",
+            Key::StackBacktrace => "Stack backtrace",
+            Key::Crashed => "The application panicked (crashed).",
+            Key::Message => "Message",
+            Key::Location => "Location",
+            Key::Metadata => "METADATA",
+            Key::Context => "CONTEXT",
+            Key::Backtrace => "BACKTRACE",
+            Key::BacktraceNotRequested => "Backtrace omitted. Run with RUST_BACKTRACE=1 environment variable to display it.",
+            Key::BacktraceDisabled => "Backtrace omitted. Enable `backtrace-on` feature of lazy-panic to display it.",
+            Key::ReportIssue => "Consider reporting this issue along with the above information.",
+            Key::Embarrassing => "Well, this is embarrassing.",
+            Key::ReportWritten => "{app} had a problem and crashed. Report has been written to \"{path}\".",
+            Key::ReportInstructions => "Please submit the report to developers, along with steps to reproduce the crash.",
+            Key::ReportFailed => "Failed to write crash report to \"{path}\": {error}",
+        }
+    }
+}
+
+static TABLE: RwLock<Vec<(Key, String)>> = RwLock::new(Vec::new());
+
+//Table is global, so tests that change it or rely on its texts must hold this lock.
+#[cfg(test)]
+pub static TEST_LOCK: ::std::sync::Mutex<()> = ::std::sync::Mutex::new(());
+
+///Sets translation of `key`, replacing previous one.
+pub fn set<T: Into<String>>(key: Key, text: T) {
+    let text = text.into();
+    let mut table = TABLE.write().unwrap_or_else(|error| error.into_inner());
+
+    match table.iter_mut().find(|entry| entry.0 == key) {
+        Some(entry) => entry.1 = text,
+        None => table.push((key, text)),
+    }
+}
+
+///Sets translations of table's keys.
+pub fn register(table: &[(Key, &str)]) {
+    for &(key, text) in table {
+        set(key, text);
+    }
+}
+
+///Removes all translations.
+pub fn reset() {
+    TABLE.write().unwrap_or_else(|error| error.into_inner()).clear();
+}
+
+///Returns translation of `key` or its [default text](enum.Key.html#method.default_text)
+pub fn text(key: Key) -> Cow<'static, str> {
+    let table = TABLE.read().unwrap_or_else(|error| error.into_inner());
+    match table.iter().find(|entry| entry.0 == key) {
+        Some(entry) => Cow::Owned(entry.1.clone()),
+        None => Cow::Borrowed(key.default_text()),
+    }
+}
+
+///Returns translation of `key` with `{name}` placeholders replaced by values.
+///
+///Placeholders are replaced in single pass, so values are written as they are.
+///Unknown placeholders are kept.
+pub fn format(key: Key, values: &[(&str, &str)]) -> String {
+    let text = text(key);
+    let mut result = String::with_capacity(text.len());
+    let mut rest = &text[..];
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            values.iter().find(|value| value.0 == name).map(|value| (end, value.1))
+        });
+        match value {
+            Some((end, value)) => {
+                result.push_str(value);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                result.push('{');
+                rest = &rest[1..];
+            },
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use lock;
+    use super::{format, set, text, Key, TEST_LOCK};
+
+    #[test]
+    fn should_translate_text() {
+        let _lock = lock(&TEST_LOCK);
+        assert_eq!(text(Key::Embarrassing), "Well, this is embarrassing.");
+
+        set(Key::ReportFailed, "Bericht \"{path}\" fehlgeschlagen: {error}");
+        assert_eq!(format(Key::ReportFailed, &[("path", "/tmp"), ("error", "lolka")]), "Bericht \"/tmp\" fehlgeschlagen: lolka");
+        set(Key::ReportFailed, Key::ReportFailed.default_text());
+    }
+
+    #[test]
+    fn should_not_expand_values() {
+        let _lock = lock(&TEST_LOCK);
+        assert_eq!(format(Key::ReportWritten, &[("app", "{path}"), ("path", "/tmp/{error}")]),
+                   "{path} had a problem and crashed. Report has been written to \"/tmp/{error}\".");
+        assert_eq!(format(Key::ReportWritten, &[("path", "{{path}")]),
+                   "{app} had a problem and crashed. Report has been written to \"{{path}\".");
+    }
+}
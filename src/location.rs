@@ -0,0 +1,111 @@
+//! Formatting of panic locations.
+//!
+//!Paths written by built-in formatters can be shortened by stripping configured prefixes,
+//!so that reports are not dominated by absolute paths of build machine.
+
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use formatter;
+use lock;
+
+static PREFIXES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+static STRIP_RUSTC: AtomicBool = AtomicBool::new(false);
+
+///Registers prefix to be stripped from file paths (e.g. workspace root).
+///
+///Prefixes are tried in order of registration and only the first matching one is stripped.
+pub fn strip_prefix<T: Into<String>>(prefix: T) {
+    lock(&PREFIXES).push(prefix.into());
+}
+
+///Enables stripping of `/rustc/<commit hash>/` prefix of standard library's paths.
+pub fn strip_rustc_prefix() {
+    STRIP_RUSTC.store(true, Ordering::Release);
+}
+
+///Removes all registered prefixes and disables stripping of rustc's prefix.
+pub fn reset() {
+    lock(&PREFIXES).clear();
+    STRIP_RUSTC.store(false, Ordering::Release);
+}
+
+///Returns file path with stripped prefix, if any matches.
+///
+///In [deterministic](../formatter/fn.set_deterministic.html) mode, remaining absolute path
+///is shortened to `{crate}/src/{file}`, or `{file}` if it is not within `src`.
+pub fn strip(path: &str) -> &str {
+    let path = strip_configured(path);
+    match formatter::is_deterministic() && is_absolute(path) {
+        true => shorten(path),
+        false => path,
+    }
+}
+
+fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/') || path.starts_with('\\') || (bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+fn shorten(path: &str) -> &str {
+    let is_separator = |ch| ch == '/' || ch == '\\';
+    let start = match path.rfind("/src/").or_else(|| path.rfind("\\src\\")) {
+        Some(src) => path[..src].rfind(is_separator),
+        None => path.rfind(is_separator),
+    };
+
+    match start {
+        Some(start) => &path[start + 1..],
+        None => path,
+    }
+}
+
+fn strip_configured(path: &str) -> &str {
+    if STRIP_RUSTC.load(Ordering::Acquire) {
+        if let Some(path) = strip_rustc(path) {
+            return path;
+        }
+    }
+
+    for prefix in lock(&PREFIXES).iter() {
+        if path.starts_with(prefix.as_str()) {
+            return &path[prefix.len()..];
+        }
+    }
+
+    path
+}
+
+fn strip_rustc(path: &str) -> Option<&str> {
+    const RUSTC: &str = "/rustc/";
+
+    if !path.starts_with(RUSTC) {
+        return None;
+    }
+
+    let path = &path[RUSTC.len()..];
+    let hash_len = path.find('/')?;
+    match hash_len > 0 && path[..hash_len].bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        true => Some(&path[hash_len + 1..]),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_rustc, shorten};
+
+    #[test]
+    fn should_strip_rustc() {
+        assert_eq!(strip_rustc("/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/ops/function.rs"), Some("library/core/src/ops/function.rs"));
+        assert_eq!(strip_rustc("/rustc/not-a-hash/library/core/src/lib.rs"), None);
+        assert_eq!(strip_rustc("/home/rustc/src/main.rs"), None);
+    }
+
+    #[test]
+    fn should_shorten_absolute_path() {
+        assert_eq!(shorten("/home/user/.cargo/registry/src/index.crates.io-1949cf8c6b5b557f/log-0.4.22/src/lib.rs"), "log-0.4.22/src/lib.rs");
+        assert_eq!(shorten("C:\\Users\\user\\app\\src\\main.rs"), "app\\src\\main.rs");
+        assert_eq!(shorten("/tmp/build/main.rs"), "main.rs");
+    }
+}
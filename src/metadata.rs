@@ -0,0 +1,93 @@
+//! Application metadata included into panic reports.
+
+use std::sync::Mutex;
+
+use lock;
+
+static APP_INFO: Mutex<Option<(&'static str, &'static str)>> = Mutex::new(None);
+static BUILD_INFO: Mutex<Option<BuildInfo>> = Mutex::new(None);
+static ENTRIES: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+const BUILD_ID_KEY: &str = "build";
+
+///Registers application's name and version.
+///
+///Prefer to use [app_info!](../macro.app_info.html) which captures them from calling crate.
+pub fn set_app_info(name: &'static str, version: &'static str) {
+    *lock(&APP_INFO) = Some((name, version));
+}
+
+///Returns registered application's name and version, if any.
+pub fn app_info() -> Option<(&'static str, &'static str)> {
+    *lock(&APP_INFO)
+}
+
+///Build information of application.
+#[derive(Clone, Copy, Debug)]
+pub struct BuildInfo {
+    ///Compilation profile (e.g. `debug` or `release`)
+    pub profile: &'static str,
+    ///Optimization level.
+    pub opt_level: &'static str,
+    ///Version of rustc used to build application.
+    pub rustc: &'static str,
+}
+
+///Registers build information.
+///
+///Prefer to use [build_info!](../macro.build_info.html) which captures it from calling crate.
+pub fn set_build_info(profile: &'static str, opt_level: &'static str, rustc: &'static str) {
+    *lock(&BUILD_INFO) = Some(BuildInfo {
+        profile,
+        opt_level,
+        rustc,
+    });
+}
+
+///Returns registered build information, if any.
+pub fn build_info() -> Option<BuildInfo> {
+    *lock(&BUILD_INFO)
+}
+
+///Registers build identifier (e.g. git commit or CI build number).
+///
+///Stored as metadata entry with key `build`, so it is included by all built-in formatters.
+pub fn set_build_id<T: Into<String>>(id: T) {
+    insert(BUILD_ID_KEY, id);
+}
+
+///Returns registered build identifier, if any.
+pub fn build_id() -> Option<String> {
+    get(BUILD_ID_KEY)
+}
+
+///Inserts metadata entry, replacing value of already existing key.
+///
+///Entries are included by all built-in formatters in order of their insertion.
+pub fn insert<K: Into<String>, V: Into<String>>(key: K, value: V) {
+    let key = key.into();
+    let value = value.into();
+    let mut entries = lock(&ENTRIES);
+
+    match entries.iter_mut().find(|entry| entry.0 == key) {
+        Some(entry) => entry.1 = value,
+        None => entries.push((key, value)),
+    }
+}
+
+///Removes metadata entry, returning its value.
+pub fn remove(key: &str) -> Option<String> {
+    let mut entries = lock(&ENTRIES);
+    let idx = entries.iter().position(|entry| entry.0 == key)?;
+    Some(entries.remove(idx).1)
+}
+
+///Returns value of metadata entry, if any.
+pub fn get(key: &str) -> Option<String> {
+    lock(&ENTRIES).iter().find(|entry| entry.0 == key).map(|entry| entry.1.clone())
+}
+
+///Returns all metadata entries in order of insertion.
+pub fn entries() -> Vec<(String, String)> {
+    lock(&ENTRIES).clone()
+}
@@ -0,0 +1,36 @@
+//! Delivery of panics to external services.
+//!
+//!Notifiers are invoked by [Notify](../formatter/struct.Notify.html) after panic is printed.
+//!Delivery happens synchronously within panic hook, so it should be bounded by timeout.
+
+use PanicHookInfo;
+
+pub mod prometheus;
+pub mod report;
+
+#[cfg(feature = "ureq")]
+mod http;
+#[cfg(any(feature = "slack", feature = "discord", feature = "email"))]
+mod summary;
+#[cfg(feature = "sentry")]
+pub mod sentry;
+#[cfg(feature = "minidump")]
+pub mod minidump;
+#[cfg(feature = "slack")]
+pub mod slack;
+#[cfg(feature = "discord")]
+pub mod discord;
+#[cfg(feature = "email")]
+pub mod email;
+#[cfg(feature = "desktop")]
+pub mod desktop;
+#[cfg(feature = "otel")]
+pub mod otel;
+#[cfg(all(feature = "wer", windows))]
+pub mod wer;
+
+///Describes how to deliver panic to external service.
+pub trait Notifier {
+    ///Delivers panic, ignoring any failure.
+    fn notify(info: &PanicHookInfo);
+}
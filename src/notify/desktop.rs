@@ -0,0 +1,125 @@
+//! Desktop notification of panics.
+//!
+//!Notification is raised via platform's tools:
+//!
+//!- Linux and BSD - `notify-send`
+//!- macOS - `osascript`
+//!- Windows - PowerShell's balloon tip.
+//!
+//!```rust,no_run
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::desktop::{self, DesktopNotifier};
+//!
+//!fn main() {
+//!    app_info!();
+//!    desktop::set_report_path("/home/user/.local/share/my-app/crash.log");
+//!    set_panic_message!(Notify<Simple, DesktopNotifier>);
+//!}
+//!```
+
+use std::io;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use PanicHookInfo;
+
+use lock;
+use metadata;
+use payload;
+use super::Notifier;
+
+static REPORT_PATH: Mutex<Option<String>> = Mutex::new(None);
+
+///Sets path of report to mention in notification.
+///
+///Otherwise notification contains panic's message.
+pub fn set_report_path<T: Into<String>>(path: T) {
+    *lock(&REPORT_PATH) = Some(path.into());
+}
+
+///Raises desktop notification `{App} crashed` with path of report or panic's message.
+///
+///Failure to notify, e.g. due to missing tool, is ignored.
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify(info: &PanicHookInfo) {
+        let (title, body) = message(info);
+        let _ = show(&title, &body);
+    }
+}
+
+fn message(info: &PanicHookInfo) -> (String, String) {
+    let title = match metadata::app_info() {
+        Some((name, _)) => format!("{} crashed", name),
+        None => "Application crashed".to_owned(),
+    };
+    let body = match lock(&REPORT_PATH).as_ref() {
+        Some(path) => format!("Report saved to {}", path),
+        None => payload::to_string(info.payload()),
+    };
+
+    (title, body)
+}
+
+//Text is passed via arguments or environment, so that no escaping is needed.
+//Options end at `--`, so that text starting with `-` is not parsed as option.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show(title: &str, body: &str) -> io::Result<()> {
+    Command::new("notify-send").args(["--urgency=critical", "--app-name=lazy-panic", "--", title, body])
+                               .stdout(Stdio::null())
+                               .stderr(Stdio::null())
+                               .status()
+                               .map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn show(title: &str, body: &str) -> io::Result<()> {
+    Command::new("osascript").args(["-e", "display notification (system attribute \"LAZY_PANIC_BODY\") with title (system attribute \"LAZY_PANIC_TITLE\")"])
+                             .env("LAZY_PANIC_TITLE", title)
+                             .env("LAZY_PANIC_BODY", body)
+                             .stdout(Stdio::null())
+                             .stderr(Stdio::null())
+                             .status()
+                             .map(|_| ())
+}
+
+#[cfg(windows)]
+fn show(title: &str, body: &str) -> io::Result<()> {
+    const SCRIPT: &str = "Add-Type -AssemblyName System.Windows.Forms; \
+                          $icon = New-Object System.Windows.Forms.NotifyIcon; \
+                          $icon.Icon = [System.Drawing.SystemIcons]::Error; \
+                          $icon.Visible = $true; \
+                          $icon.ShowBalloonTip(10000, $env:LAZY_PANIC_TITLE, $env:LAZY_PANIC_BODY, 'Error'); \
+                          Start-Sleep -Seconds 10; \
+                          $icon.Dispose()";
+
+    //Balloon must outlive process, hence it is not waited.
+    Command::new("powershell").args(["-NoProfile", "-NonInteractive", "-WindowStyle", "Hidden", "-Command", SCRIPT])
+                              .env("LAZY_PANIC_TITLE", title)
+                              .env("LAZY_PANIC_BODY", body)
+                              .stdout(Stdio::null())
+                              .stderr(Stdio::null())
+                              .spawn()
+                              .map(|_| ())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn show(_: &str, _: &str) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use test::with_panic_info;
+    use super::message;
+
+    #[test]
+    fn should_build_message() {
+        let (title, body) = with_panic_info("lolka", message);
+        assert!(title.ends_with("crashed"), "{}", title);
+        assert_eq!(body, "lolka");
+    }
+}
@@ -0,0 +1,93 @@
+//! Posting of panics to Discord.
+//!
+//!Webhook URL is taken from [set_webhook](fn.set_webhook.html) or `LAZY_PANIC_DISCORD_WEBHOOK`
+//!environment variable.
+//!
+//!```rust,no_run
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::discord::DiscordNotifier;
+//!
+//!fn main() {
+//!    app_info!();
+//!    lazy_panic::notify::discord::set_webhook("https://discord.com/api/webhooks/0/XXXX");
+//!    set_panic_message!(Notify<Simple, DiscordNotifier>);
+//!}
+//!```
+
+use std::env;
+use std::sync::Mutex;
+use PanicHookInfo;
+
+use formatter::escape::escape_json;
+use lock;
+use super::Notifier;
+use super::http;
+use super::summary::Summary;
+
+//Discord limits embed's description to 4096 characters.
+const MAX_MESSAGE_LEN: usize = 4000;
+const COLOR_RED: u32 = 0xe7_4c_3c;
+
+static WEBHOOK: Mutex<Option<String>> = Mutex::new(None);
+
+///Sets URL of Discord webhook, overriding `LAZY_PANIC_DISCORD_WEBHOOK`
+pub fn set_webhook<T: Into<String>>(url: T) {
+    *lock(&WEBHOOK) = Some(url.into());
+}
+
+fn webhook() -> Option<String> {
+    match lock(&WEBHOOK).clone() {
+        Some(url) => Some(url),
+        None => env::var("LAZY_PANIC_DISCORD_WEBHOOK").ok(),
+    }
+}
+
+///Posts crash summary with application, message, location and fingerprint to Discord as embed.
+///
+///Does nothing if webhook is not configured.
+pub struct DiscordNotifier;
+
+impl Notifier for DiscordNotifier {
+    fn notify(info: &PanicHookInfo) {
+        if let Some(url) = webhook() {
+            let _ = http::post(&url, &[], "application/json", &message(&Summary::new(info)));
+        }
+    }
+}
+
+fn message(summary: &Summary) -> String {
+    let text = match summary.message.char_indices().nth(MAX_MESSAGE_LEN) {
+        Some((end, _)) => format!("{}…", &summary.message[..end]),
+        None => summary.message.clone(),
+    };
+    //Prevents message from closing code block.
+    let text = text.replace("```", "`\u{200b}``");
+
+    format!("{{\"embeds\":[{{\"title\":\"{} panicked\",\"description\":\"```\\n{}\\n```\",\"color\":{},\"fields\":[\
+             {{\"name\":\"Location\",\"value\":\"`{}`\",\"inline\":true}},\
+             {{\"name\":\"Fingerprint\",\"value\":\"`{:016x}`\",\"inline\":true}}]}}]}}",
+            escape_json(&summary.app), escape_json(&text), COLOR_RED, escape_json(&summary.location), summary.fingerprint)
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::summary::Summary;
+    use super::message;
+
+    #[test]
+    fn should_build_message() {
+        let summary = Summary {
+            app: "game 1.0.0".to_owned(),
+            location: "src/main.rs:1".to_owned(),
+            message: "\"boom\"".to_owned(),
+            fingerprint: 0xff,
+        };
+
+        assert_eq!(message(&summary), "{\"embeds\":[{\"title\":\"game 1.0.0 panicked\",\"description\":\"```\\n\\\"boom\\\"\\n```\",\"color\":15158332,\"fields\":[\
+                                       {\"name\":\"Location\",\"value\":\"`src/main.rs:1`\",\"inline\":true},\
+                                       {\"name\":\"Fingerprint\",\"value\":\"`00000000000000ff`\",\"inline\":true}]}]}");
+    }
+}
@@ -0,0 +1,284 @@
+//! Email notification of panics.
+//!
+//!Report is handed either to local `sendmail` or to SMTP relay, without authentication or TLS.
+//!Intended for on-premise deployments where email is the only channel out.
+//!
+//!```rust,no_run
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::email::{self, EmailNotifier, Transport};
+//!
+//!fn main() {
+//!    app_info!();
+//!    email::set_transport(Transport::Smtp("mail.internal:25".to_owned()));
+//!    email::set_sender("my-app@internal");
+//!    email::set_recipients(vec!["oncall@internal"]);
+//!    set_panic_message!(Notify<Simple, EmailNotifier>);
+//!}
+//!```
+
+use std::io::{self, BufRead, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::Duration;
+use PanicHookInfo;
+
+use formatter::{Host, PanicInfo, Prefix, Simple, Suffix};
+use lock;
+use super::Notifier;
+use super::summary::Summary;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_HEADLINE_LEN: usize = 80;
+//Encoded word is limited to 75 characters, so that 45 bytes go as 60 characters of base64.
+const MAX_ENCODED_WORD_LEN: usize = 45;
+
+///Describes how to deliver email.
+#[derive(Clone, Debug)]
+pub enum Transport {
+    ///Path to `sendmail` compatible executable, invoked as `sendmail -t -oi`
+    Sendmail(PathBuf),
+    ///Address of SMTP relay as `host:port`
+    Smtp(String),
+}
+
+struct Config {
+    transport: Option<Transport>,
+    sender: Option<String>,
+    recipients: Vec<String>,
+}
+
+static CONFIG: Mutex<Config> = Mutex::new(Config {
+    transport: None,
+    sender: None,
+    recipients: Vec::new(),
+});
+
+///Sets transport, which is `/usr/sbin/sendmail` by default.
+pub fn set_transport(transport: Transport) {
+    lock(&CONFIG).transport = Some(transport);
+}
+
+///Sets sender's address, which is `lazy-panic@localhost` by default.
+pub fn set_sender<T: Into<String>>(sender: T) {
+    lock(&CONFIG).sender = Some(sender.into());
+}
+
+///Sets recipients' addresses.
+pub fn set_recipients<I: IntoIterator<Item = T>, T: Into<String>>(recipients: I) {
+    lock(&CONFIG).recipients = recipients.into_iter().map(Into::into).collect();
+}
+
+///Sends crash report by email.
+///
+///Subject is application, location and first line of panic's message, while body is the same as of
+///[Simple](../../formatter/struct.Simple.html) prefixed with [Host](../../formatter/struct.Host.html).
+///
+///Does nothing if there are no recipients.
+pub struct EmailNotifier;
+
+impl Notifier for EmailNotifier {
+    fn notify(info: &PanicHookInfo) {
+        let (transport, sender, recipients) = {
+            let config = lock(&CONFIG);
+            if config.recipients.is_empty() {
+                return;
+            }
+
+            let transport = config.transport.clone().unwrap_or_else(|| Transport::Sendmail(PathBuf::from("/usr/sbin/sendmail")));
+            let sender = config.sender.clone().unwrap_or_else(|| "lazy-panic@localhost".to_owned());
+            (transport, sender, config.recipients.clone())
+        };
+
+        let sender = strip_line_breaks(&sender);
+        let recipients = recipients.iter().map(|recipient| strip_line_breaks(recipient)).collect::<Vec<_>>();
+        let message = message(info, &sender, &recipients);
+        let _ = match transport {
+            Transport::Sendmail(path) => send_sendmail(&path, &message),
+            Transport::Smtp(address) => send_smtp(&address, &sender, &recipients, &message),
+        };
+    }
+}
+
+fn message(info: &PanicHookInfo, sender: &str, recipients: &[String]) -> String {
+    let summary = Summary::new(info);
+
+    let mut body = Vec::new();
+    let _ = Host::write_in(&mut body);
+    let _ = <Simple as PanicInfo>::write_in(&mut body, info);
+    let _ = <Simple as Suffix>::write_in(&mut body);
+    let body = String::from_utf8_lossy(&body);
+
+    let headline = summary.message.lines().next().unwrap_or("").chars().take(MAX_HEADLINE_LEN).collect::<String>();
+    let subject = format!("{} panicked at {}: {}", summary.app, summary.location, headline);
+
+    let mut message = format!("From: {}\r\nTo: {}\r\nSubject: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+                              strip_line_breaks(sender), strip_line_breaks(&recipients.join(", ")), encode_header(&strip_line_breaks(&subject)));
+    for line in body.lines() {
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push_str(&format!("\r\nFingerprint: {:016x}\r\n", summary.fingerprint));
+    message
+}
+
+//Removes CR and LF, so that value cannot inject headers or SMTP commands.
+fn strip_line_breaks(value: &str) -> String {
+    value.chars().filter(|&ch| ch != '\r' && ch != '\n').collect()
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (idx, &byte)| group | u32::from(byte) << (16 - idx * 8));
+        for idx in 0..4 {
+            match idx <= chunk.len() {
+                true => result.push(ALPHABET[(group >> (18 - idx * 6)) as usize & 0x3f] as char),
+                false => result.push('='),
+            }
+        }
+    }
+    result
+}
+
+//Encodes non-ASCII value as RFC 2047 encoded words, folded over lines.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_owned();
+    }
+
+    let mut words = Vec::new();
+    let mut start = 0;
+    while start < value.len() {
+        //Characters are not split between words.
+        let mut end = (start + MAX_ENCODED_WORD_LEN).min(value.len());
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        words.push(format!("=?utf-8?B?{}?=", base64(&value.as_bytes()[start..end])));
+        start = end;
+    }
+    words.join("\r\n ")
+}
+
+fn send_sendmail(path: &Path, message: &str) -> io::Result<()> {
+    let mut child = Command::new(path).args(["-t", "-oi"]).stdin(Stdio::piped()).stdout(Stdio::null()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(message.as_bytes())?;
+    }
+
+    match child.wait()?.success() {
+        true => Ok(()),
+        false => Err(io::Error::other("sendmail failed")),
+    }
+}
+
+fn expect_reply<R: BufRead>(reader: &mut R, code: char) -> io::Result<()> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        //Multi-line reply continues with `-` after code.
+        if line.as_bytes().get(3) != Some(&b'-') {
+            break;
+        }
+    }
+
+    match line.starts_with(code) {
+        true => Ok(()),
+        false => Err(io::Error::other(line.trim_end().to_owned())),
+    }
+}
+
+fn send_smtp(address: &str, sender: &str, recipients: &[String], message: &str) -> io::Result<()> {
+    let address = address.to_socket_addrs()?.next().ok_or(io::ErrorKind::NotFound)?;
+    let stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let mut reader = io::BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    expect_reply(&mut reader, '2')?;
+    writer.write_all(b"HELO localhost\r\n")?;
+    expect_reply(&mut reader, '2')?;
+    write!(writer, "MAIL FROM:<{}>\r\n", sender)?;
+    expect_reply(&mut reader, '2')?;
+    for recipient in recipients {
+        write!(writer, "RCPT TO:<{}>\r\n", recipient)?;
+        expect_reply(&mut reader, '2')?;
+    }
+    writer.write_all(b"DATA\r\n")?;
+    expect_reply(&mut reader, '3')?;
+    for line in message.split("\r\n") {
+        //Dot-stuffing, so that line is not taken as end of data.
+        if line.starts_with('.') {
+            writer.write_all(b".")?;
+        }
+        write!(writer, "{}\r\n", line)?;
+    }
+    writer.write_all(b".\r\n")?;
+    expect_reply(&mut reader, '2')?;
+    writer.write_all(b"QUIT\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::{encode_header, send_smtp, strip_line_breaks};
+
+    #[test]
+    fn should_sanitize_headers() {
+        assert_eq!(strip_line_breaks("lolka\r\nBcc: all@test"), "lolkaBcc: all@test");
+        assert_eq!(encode_header("app panicked at src/main.rs:1: lolka"), "app panicked at src/main.rs:1: lolka");
+        assert_eq!(encode_header("Ёлка"), "=?utf-8?B?0IHQu9C60LA=?=");
+
+        let encoded = encode_header(&"Ё".repeat(30));
+        assert_eq!(encoded, "=?utf-8?B?0IHQgdCB0IHQgdCB0IHQgdCB0IHQgdCB0IHQgdCB0IHQgdCB0IHQgdCB0IE=?=\r\n =?utf-8?B?0IHQgdCB0IHQgdCB0IHQgQ==?=");
+    }
+
+    #[test]
+    fn should_send_via_smtp() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("To bind");
+        let address = listener.local_addr().expect("To get address").to_string();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().expect("To accept");
+            let mut reader = BufReader::new(stream.try_clone().expect("To clone"));
+            let mut writer = stream;
+            let mut received = Vec::new();
+
+            writer.write_all(b"220 test\r\n").unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                received.push(line.trim_end().to_owned());
+                match line.as_str() {
+                    "DATA\r\n" => writer.write_all(b"354 go\r\n").unwrap(),
+                    "QUIT\r\n" => break,
+                    ".\r\n" | "HELO localhost\r\n" => writer.write_all(b"250-ok\r\n250 ok\r\n").unwrap(),
+                    line if line.starts_with("MAIL") || line.starts_with("RCPT") => writer.write_all(b"250 ok\r\n").unwrap(),
+                    _ => (),
+                }
+                line.clear();
+            }
+            received
+        });
+
+        send_smtp(&address, "app@test", &["oncall@test".to_owned()], "Subject: boom\r\n\r\n.hidden\r\n").expect("To send");
+        let received = server.join().expect("To join server");
+        assert_eq!(received, ["HELO localhost", "MAIL FROM:<app@test>", "RCPT TO:<oncall@test>", "DATA", "Subject: boom", "", "..hidden", "", ".", "QUIT"]);
+    }
+}
@@ -0,0 +1,21 @@
+//! Minimal blocking HTTP submission.
+
+use std::io;
+use std::time::Duration;
+
+use ureq;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+///Posts `body` to `url`, failing on non-success status.
+pub fn post(url: &str, headers: &[(&str, &str)], content_type: &str, body: &str) -> io::Result<()> {
+    let mut request = ureq::post(url).timeout(TIMEOUT).set("Content-Type", content_type);
+    for &(name, value) in headers {
+        request = request.set(name, value);
+    }
+
+    match request.send_string(body) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(io::Error::other(error.to_string())),
+    }
+}
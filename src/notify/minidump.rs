@@ -0,0 +1,177 @@
+//! Minidump of process on panic.
+//!
+//!Dump is written into [directory](fn.set_directory.html) as `{pid}-{timestamp}.dmp`, so that
+//!post-mortem debugging is possible with standard tooling (e.g. `minidump-stackwalk`) even for
+//!optimized builds.
+//!
+//!Supported on Linux, Windows and macOS. On other platforms dump is not written.
+//!
+//!On Linux process is forked, and the child dumps its parent via `ptrace`, which must be permitted
+//!(e.g. by `kernel.yama.ptrace_scope`). As child is forked from multi-threaded process, dumping
+//!might deadlock if another thread held allocator's lock at the moment of panic.
+//!
+//!```rust,no_run
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::minidump::Minidump;
+//!
+//!fn main() {
+//!    lazy_panic::notify::minidump::set_directory("/var/crash/my-app");
+//!    set_panic_message!(Notify<Simple, Minidump>);
+//!}
+//!```
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use PanicHookInfo;
+
+use lock;
+use super::Notifier;
+
+static DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+static LAST_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+///Sets directory to write dumps into.
+///
+///Defaults to temporary directory of the system.
+pub fn set_directory<P: Into<PathBuf>>(directory: P) {
+    *lock(&DIRECTORY) = Some(directory.into());
+}
+
+///Returns path of the last written dump, if any.
+pub fn last_path() -> Option<PathBuf> {
+    lock(&LAST_PATH).clone()
+}
+
+///Writes minidump of process and its path to stderr.
+pub struct Minidump;
+
+impl Notifier for Minidump {
+    fn notify(_: &PanicHookInfo) {
+        let directory = lock(&DIRECTORY).clone().unwrap_or_else(env::temp_dir);
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let path = directory.join(format!("{}-{}.dmp", process::id(), timestamp.as_secs()));
+
+        let stderr = io::stderr();
+        let mut stderr = stderr.lock();
+        match fs::create_dir_all(&directory).and_then(|_| write_minidump(&path)) {
+            Ok(()) => {
+                let _ = writeln!(stderr, "Minidump: {}", path.display());
+                *lock(&LAST_PATH) = Some(path);
+            },
+            Err(error) => {
+                let _ = writeln!(stderr, "Minidump: failed to write {}: {}", path.display(), error);
+            },
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn write_minidump(path: &Path) -> io::Result<()> {
+    use minidump_writer::minidump_writer::MinidumpWriterConfig;
+    use libc;
+    use origin;
+
+    let mut file = fs::File::create(path)?;
+    let pid = process::id() as libc::pid_t;
+    //Panic is written on behalf of panicking thread, which is the one to blame.
+    let tid = match origin::os_thread_id() {
+        Some(tid) => tid as libc::pid_t,
+        None => unsafe { libc::syscall(libc::SYS_gettid) as libc::pid_t },
+    };
+
+    //Child waits until it is permitted to trace parent.
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    match unsafe { libc::fork() } {
+        -1 => {
+            let error = io::Error::last_os_error();
+            unsafe {
+                libc::close(fds[0]);
+                libc::close(fds[1]);
+            }
+            Err(error)
+        },
+        0 => {
+            let mut ready = 0u8;
+            let code = unsafe {
+                libc::close(fds[1]);
+                libc::read(fds[0], &mut ready as *mut u8 as *mut libc::c_void, 1);
+                match MinidumpWriterConfig::new(pid, tid).write(&mut file) {
+                    Ok(_) => 0,
+                    Err(_) => 1,
+                }
+            };
+            unsafe { libc::_exit(code) }
+        },
+        child => {
+            let mut status = 0;
+            unsafe {
+                libc::prctl(libc::PR_SET_PTRACER, child as libc::c_ulong, 0, 0, 0);
+                libc::close(fds[0]);
+                libc::write(fds[1], b"1".as_ptr() as *const libc::c_void, 1);
+                libc::close(fds[1]);
+                libc::waitpid(child, &mut status, 0);
+            }
+
+            match libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0 {
+                true => Ok(()),
+                false => Err(io::Error::other("dumping process failed")),
+            }
+        },
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn write_minidump(path: &Path) -> io::Result<()> {
+    use minidump_writer::minidump_writer::MinidumpWriter;
+
+    let mut file = fs::File::create(path)?;
+    MinidumpWriter::dump_local_context(None, None, None, &mut file).map_err(|error| io::Error::other(error.to_string()))
+}
+
+#[cfg(target_os = "macos")]
+fn write_minidump(path: &Path) -> io::Result<()> {
+    use minidump_writer::minidump_writer::MinidumpWriter;
+
+    let mut file = fs::File::create(path)?;
+    match MinidumpWriter::new(None, None).dump(&mut file) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(io::Error::other(error.to_string())),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "windows", target_os = "macos")))]
+fn write_minidump(_: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "minidump is not supported on this platform"))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use super::write_minidump;
+
+    #[test]
+    fn should_write_minidump() {
+        let path = env::temp_dir().join(format!("lazy-panic-test-{}.dmp", process::id()));
+        let result = write_minidump(&path);
+        let dump = fs::read(&path).unwrap_or_default();
+        let _ = fs::remove_file(&path);
+
+        result.expect("To write minidump");
+        assert_eq!(&dump[..4], b"MDMP");
+    }
+}
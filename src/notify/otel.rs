@@ -0,0 +1,188 @@
+//! Export of panics as OpenTelemetry log records.
+//!
+//!Record is emitted via logger registered by [set_logger](fn.set_logger.html), so that crashes
+//!appear in the same backend as traces.
+//!
+//!```rust,ignore
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use opentelemetry::logs::LoggerProvider;
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::otel::{self, OtelNotifier};
+//!
+//!fn main() {
+//!    let provider = opentelemetry_sdk::logs::SdkLoggerProvider::builder().build();
+//!    otel::set_logger(provider.logger("my-app"));
+//!    set_panic_message!(Notify<Simple, OtelNotifier>);
+//!}
+//!```
+
+use std::sync::RwLock;
+use std::time::SystemTime;
+use PanicHookInfo;
+
+use opentelemetry::Context;
+use opentelemetry::logs::{AnyValue, LogRecord, Logger, Severity};
+use opentelemetry::trace::TraceContextExt;
+
+use formatter::Debug;
+use location;
+use payload;
+use super::Notifier;
+
+type EmitFn = Box<dyn Fn(&PanicHookInfo) + Send + Sync>;
+
+static EMIT: RwLock<Option<EmitFn>> = RwLock::new(None);
+
+///Registers logger to export panics through.
+pub fn set_logger<L: Logger + Send + Sync + 'static>(logger: L) {
+    let emit = move |info: &PanicHookInfo| {
+        let mut record = logger.create_log_record();
+        fill(&mut record, info);
+        logger.emit(record);
+    };
+
+    *EMIT.write().unwrap_or_else(|error| error.into_inner()) = Some(Box::new(emit));
+}
+
+///Exports panic as log record of `FATAL` severity.
+///
+///Record carries panic's message as body, `exception.*` and `code.*` attributes along with
+///trace context of the current span.
+///`exception.stacktrace` is set only with `backtrace-on` feature.
+///
+///Does nothing if logger is not registered.
+pub struct OtelNotifier;
+
+impl Notifier for OtelNotifier {
+    fn notify(info: &PanicHookInfo) {
+        if let Some(emit) = EMIT.read().unwrap_or_else(|error| error.into_inner()).as_ref() {
+            emit(info);
+        }
+    }
+}
+
+fn fill<R: LogRecord>(record: &mut R, info: &PanicHookInfo) {
+    let message = payload::to_string(info.payload());
+    let now = SystemTime::now();
+
+    record.set_event_name("panic");
+    record.set_target("panic");
+    record.set_timestamp(now);
+    record.set_observed_timestamp(now);
+    record.set_severity_number(Severity::Fatal);
+    record.set_severity_text("FATAL");
+    record.set_body(AnyValue::from(message.clone()));
+
+    record.add_attribute("exception.type", "panic");
+    record.add_attribute("exception.message", message);
+    if let Some(location) = info.location() {
+        record.add_attribute("code.file.path", location::strip(location.file()).to_owned());
+        record.add_attribute("code.line.number", i64::from(location.line()));
+        record.add_attribute("code.column.number", i64::from(location.column()));
+    }
+
+    let mut backtrace = Vec::new();
+    let _ = Debug::write_untranslated(&mut backtrace);
+    if !backtrace.is_empty() {
+        record.add_attribute("exception.stacktrace", String::from_utf8_lossy(&backtrace).into_owned());
+    }
+
+    let context = Context::current();
+    let span = context.span();
+    let span = span.span_context();
+    if span.is_valid() {
+        record.set_trace_context(span.trace_id(), span.span_id(), Some(span.trace_flags()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::SystemTime;
+
+    use opentelemetry::Key;
+    use opentelemetry::logs::{AnyValue, LogRecord, Logger, Severity};
+
+    use formatter::{Notify, Simple, Empty};
+    use hook::{install, set_thread_format};
+    use super::{set_logger, OtelNotifier};
+
+    #[derive(Default)]
+    struct Record {
+        severity: Option<Severity>,
+        attributes: Vec<(String, String)>,
+    }
+
+    impl LogRecord for Record {
+        fn set_event_name(&mut self, _: &'static str) {
+        }
+        fn set_target<T: Into<Cow<'static, str>>>(&mut self, _: T) {
+        }
+        fn set_timestamp(&mut self, _: SystemTime) {
+        }
+        fn set_observed_timestamp(&mut self, _: SystemTime) {
+        }
+        fn set_severity_text(&mut self, _: &'static str) {
+        }
+        fn set_severity_number(&mut self, number: Severity) {
+            self.severity = Some(number);
+        }
+        fn set_body(&mut self, _: AnyValue) {
+        }
+
+        fn add_attributes<I, K, V>(&mut self, attributes: I) where I: IntoIterator<Item = (K, V)>, K: Into<Key>, V: Into<AnyValue> {
+            for (key, value) in attributes {
+                self.add_attribute(key, value);
+            }
+        }
+
+        fn add_attribute<K: Into<Key>, V: Into<AnyValue>>(&mut self, key: K, value: V) {
+            let value = match value.into() {
+                AnyValue::String(value) => value.to_string(),
+                value => format!("{:?}", value),
+            };
+            self.attributes.push((key.into().to_string(), value));
+        }
+    }
+
+    struct TestLogger(Arc<Mutex<Vec<Record>>>);
+
+    impl Logger for TestLogger {
+        type LogRecord = Record;
+
+        fn create_log_record(&self) -> Record {
+            Record::default()
+        }
+
+        fn emit(&self, record: Record) {
+            self.0.lock().unwrap().push(record);
+        }
+
+        fn event_enabled(&self, _: Severity, _: &str, _: Option<&str>) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn should_export_panic() {
+        let records = Arc::new(Mutex::new(Vec::new()));
+        set_logger(TestLogger(records.clone()));
+        install::<Simple>();
+
+        let worker = thread::spawn(|| {
+            set_thread_format::<Notify<Empty, OtelNotifier>>();
+            panic!("lolka");
+        });
+        assert!(worker.join().is_err());
+
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].severity, Some(Severity::Fatal));
+        assert!(records[0].attributes.contains(&("exception.message".to_owned(), "lolka".to_owned())), "{:?}", records[0].attributes);
+        assert!(records[0].attributes.contains(&("code.file.path".to_owned(), "src/notify/otel.rs".to_owned())), "{:?}", records[0].attributes);
+    }
+}
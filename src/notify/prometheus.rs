@@ -0,0 +1,116 @@
+//! Prometheus textfile with panic metrics.
+//!
+//!File is meant to be picked up by node-exporter's textfile collector and contains:
+//!
+//!- `panic_total{fingerprint="..."}` - Counter of panics by [fingerprint](../../fingerprint/index.html)
+//!- `last_panic_timestamp_seconds` - Unix time of the last panic.
+//!
+//!Counters are read back from existing file, so that they survive restarts of process.
+//!
+//!```rust
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::prometheus::{self, Textfile};
+//!
+//!fn main() {
+//!    prometheus::set_path("/var/lib/node_exporter/textfile/my_app_panics.prom");
+//!    set_panic_message!(Notify<Simple, Textfile>);
+//!}
+//!```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use PanicHookInfo;
+
+use fingerprint;
+use lock;
+use super::Notifier;
+
+//Also serializes updates of file within process.
+static PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+///Sets path of textfile.
+///
+///File name should end with `.prom` to be collected.
+pub fn set_path<P: Into<PathBuf>>(path: P) {
+    *lock(&PATH) = Some(path.into());
+}
+
+///Updates textfile with panic.
+///
+///Does nothing if path is not set.
+pub struct Textfile;
+
+impl Notifier for Textfile {
+    fn notify(info: &PanicHookInfo) {
+        let path = lock(&PATH);
+        if let Some(path) = path.as_ref() {
+            let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+            let _ = update(path, fingerprint::fingerprint(info), timestamp.as_secs());
+        }
+    }
+}
+
+fn parse_total(line: &str) -> Option<(u64, u64)> {
+    let line = line.strip_prefix("panic_total{fingerprint=\"")?;
+    let (fingerprint, count) = line.split_once("\"} ")?;
+    Some((u64::from_str_radix(fingerprint, 16).ok()?, count.trim().parse().ok()?))
+}
+
+fn update(path: &Path, fingerprint: u64, timestamp: u64) -> io::Result<()> {
+    let mut totals = match fs::read_to_string(path) {
+        Ok(content) => content.lines().filter_map(parse_total).collect(),
+        Err(ref error) if error.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(error) => return Err(error),
+    };
+
+    match totals.iter_mut().find(|total| total.0 == fingerprint) {
+        Some(total) => total.1 += 1,
+        None => totals.push((fingerprint, 1)),
+    }
+
+    let mut content = "# HELP panic_total Total number of panics.\n# TYPE panic_total counter\n".to_owned();
+    for (fingerprint, count) in totals {
+        content.push_str(&format!("panic_total{{fingerprint=\"{:016x}\"}} {}\n", fingerprint, count));
+    }
+    content.push_str("# HELP last_panic_timestamp_seconds Unix time of the last panic.\n# TYPE last_panic_timestamp_seconds gauge\n");
+    content.push_str(&format!("last_panic_timestamp_seconds {}\n", timestamp));
+
+    //Collector must never see partially written file.
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::process;
+
+    use super::update;
+
+    #[test]
+    fn should_update_textfile() {
+        let path = env::temp_dir().join(format!("lazy-panic-test-{}.prom", process::id()));
+        let _ = fs::remove_file(&path);
+
+        update(&path, 0xff, 10).expect("To write");
+        update(&path, 0xaa, 20).expect("To write");
+        update(&path, 0xff, 30).expect("To write");
+        let content = fs::read_to_string(&path).expect("To read");
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(content, "# HELP panic_total Total number of panics.\n# TYPE panic_total counter\n\
+                             panic_total{fingerprint=\"00000000000000ff\"} 2\n\
+                             panic_total{fingerprint=\"00000000000000aa\"} 1\n\
+                             # HELP last_panic_timestamp_seconds Unix time of the last panic.\n# TYPE last_panic_timestamp_seconds gauge\n\
+                             last_panic_timestamp_seconds 30\n");
+    }
+}
@@ -0,0 +1,172 @@
+//! Crash report file for end users.
+//!
+//!On panic, JSON report with message, location, version, system information, metadata, context
+//!and, with `backtrace-on`, backtrace is written into [directory](fn.set_directory.html).
+//!Then short notice tells user where report is and how to submit it.
+//!
+//!```rust
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, JustError};
+//!use lazy_panic::notify::report::{self, ReportFile};
+//!
+//!fn main() {
+//!    app_info!();
+//!    report::set_instructions("Please attach it to new issue at https://github.com/DoumanAsh/lazy-panic.rs/issues");
+//!    set_panic_message!(Notify<JustError, ReportFile>);
+//!}
+//!```
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use PanicHookInfo;
+
+use context;
+use fingerprint;
+use formatter::{Debug, Suffix, SystemInfo};
+use formatter::escape::escape_json;
+use location;
+use locale::{self, Key};
+use lock;
+use metadata;
+use payload;
+use super::Notifier;
+
+static DIRECTORY: Mutex<Option<PathBuf>> = Mutex::new(None);
+static INSTRUCTIONS: Mutex<Option<String>> = Mutex::new(None);
+
+///Sets directory to write reports into.
+///
+///Defaults to application's directory within user's cache directory:
+///
+///- Linux and BSD - `$XDG_CACHE_HOME/{app}` or `~/.cache/{app}`
+///- macOS - `~/Library/Caches/{app}`
+///- Windows - `%LOCALAPPDATA%\{app}`
+///
+///Where `{app}` is name from [app_info](../../metadata/fn.app_info.html)
+pub fn set_directory<P: Into<PathBuf>>(directory: P) {
+    *lock(&DIRECTORY) = Some(directory.into());
+}
+
+///Sets instructions on how to submit report, written after its path.
+pub fn set_instructions<T: Into<String>>(instructions: T) {
+    *lock(&INSTRUCTIONS) = Some(instructions.into());
+}
+
+#[cfg(windows)]
+fn cache_dir() -> Option<PathBuf> {
+    env::var_os("LOCALAPPDATA").map(PathBuf::from)
+}
+
+#[cfg(target_os = "macos")]
+fn cache_dir() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join("Library").join("Caches"))
+}
+
+#[cfg(all(not(windows), not(target_os = "macos")))]
+fn cache_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CACHE_HOME") {
+        Some(cache) => Some(PathBuf::from(cache)),
+        None => env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")),
+    }
+}
+
+fn app_name() -> &'static str {
+    match metadata::app_info() {
+        Some((name, _)) => name,
+        None => "lazy-panic",
+    }
+}
+
+///Writes crash report file and notice about it to stderr.
+pub struct ReportFile;
+
+impl Notifier for ReportFile {
+    fn notify(info: &PanicHookInfo) {
+        let directory = match lock(&DIRECTORY).clone() {
+            Some(directory) => directory,
+            None => cache_dir().unwrap_or_else(env::temp_dir).join(app_name()),
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let path = directory.join(format!("report-{}-{}.json", process::id(), timestamp.as_secs()));
+
+        let stderr = io::stderr();
+        let mut stderr = stderr.lock();
+        match fs::create_dir_all(&directory).and_then(|_| fs::write(&path, report(info, timestamp.as_secs()))) {
+            Ok(()) => {
+                let instructions = match lock(&INSTRUCTIONS).clone() {
+                    Some(instructions) => instructions,
+                    None => locale::text(Key::ReportInstructions).into_owned(),
+                };
+                let path = path.display().to_string();
+                let _ = writeln!(stderr, "\n{}\n\n{}\n{}", locale::text(Key::Embarrassing),
+                                 locale::format(Key::ReportWritten, &[("app", app_name()), ("path", &path)]), instructions);
+            },
+            Err(error) => {
+                let path = path.display().to_string();
+                let _ = writeln!(stderr, "\n{}", locale::format(Key::ReportFailed, &[("path", &path), ("error", &error.to_string())]));
+            },
+        }
+    }
+}
+
+fn report(info: &PanicHookInfo, timestamp: u64) -> String {
+    let mut report = format!("{{\n  \"name\": \"{}\",\n  \"version\": \"{}\",\n  \"timestamp\": {},\n  \"message\": \"{}\",\n  \"fingerprint\": \"{:016x}\"",
+                             escape_json(app_name()),
+                             escape_json(metadata::app_info().map(|info| info.1).unwrap_or("unknown")),
+                             timestamp,
+                             escape_json(&payload::to_string(info.payload())),
+                             fingerprint::fingerprint(info));
+
+    if let Some(location) = info.location() {
+        report.push_str(&format!(",\n  \"location\": \"{}:{}:{}\"", escape_json(location::strip(location.file())), location.line(), location.column()));
+    }
+
+    let mut system = Vec::new();
+    let _ = SystemInfo::write_in(&mut system);
+    let system = String::from_utf8_lossy(&system);
+    report.push_str(&format!(",\n  \"system\": \"{}\"", escape_json(system.trim_start_matches("\nSystem: "))));
+
+    report.push_str(",\n  \"metadata\": {");
+    for (idx, (key, value)) in metadata::entries().iter().enumerate() {
+        let separator = if idx == 0 { "" } else { "," };
+        report.push_str(&format!("{}\n    \"{}\": \"{}\"", separator, escape_json(key), escape_json(value)));
+    }
+    report.push_str("\n  },\n  \"context\": [");
+    for (idx, entry) in context::entries().iter().enumerate() {
+        let separator = if idx == 0 { "" } else { "," };
+        report.push_str(&format!("{}\n    \"{}\"", separator, escape_json(entry)));
+    }
+    report.push_str("\n  ]");
+
+    let mut backtrace = Vec::new();
+    let _ = Debug::write_untranslated(&mut backtrace);
+    if !backtrace.is_empty() {
+        report.push_str(&format!(",\n  \"backtrace\": \"{}\"", escape_json(&String::from_utf8_lossy(&backtrace))));
+    }
+
+    report.push_str("\n}\n");
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use test::with_panic_info;
+    use super::report;
+
+    #[test]
+    fn should_build_report() {
+        let _context = context!("loading \"save\"");
+        let report = with_panic_info("lolka", |info| report(info, 42));
+        assert!(report.contains("\n  \"timestamp\": 42,\n  \"message\": \"lolka\",\n"), "{}", report);
+        assert!(report.contains("\n  \"location\": \"src/hook/test.rs:"), "{}", report);
+        assert!(report.contains("\n  \"system\": \"os="), "{}", report);
+        assert!(report.contains("\n  \"context\": [\n    \"loading \\\"save\\\"\"\n  ]"), "{}", report);
+    }
+}
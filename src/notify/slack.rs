@@ -0,0 +1,85 @@
+//! Posting of panics to Slack.
+//!
+//!Webhook URL is taken from [set_webhook](fn.set_webhook.html) or `LAZY_PANIC_SLACK_WEBHOOK`
+//!environment variable.
+//!
+//!```rust,no_run
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::slack::SlackNotifier;
+//!
+//!fn main() {
+//!    app_info!();
+//!    lazy_panic::notify::slack::set_webhook("https://hooks.slack.com/services/T000/B000/XXXX");
+//!    set_panic_message!(Notify<Simple, SlackNotifier>);
+//!}
+//!```
+
+use std::env;
+use std::sync::Mutex;
+use PanicHookInfo;
+
+use formatter::escape::escape_json;
+use lock;
+use super::Notifier;
+use super::http;
+use super::summary::Summary;
+
+static WEBHOOK: Mutex<Option<String>> = Mutex::new(None);
+
+///Sets URL of Slack incoming webhook, overriding `LAZY_PANIC_SLACK_WEBHOOK`
+pub fn set_webhook<T: Into<String>>(url: T) {
+    *lock(&WEBHOOK) = Some(url.into());
+}
+
+fn webhook() -> Option<String> {
+    match lock(&WEBHOOK).clone() {
+        Some(url) => Some(url),
+        None => env::var("LAZY_PANIC_SLACK_WEBHOOK").ok(),
+    }
+}
+
+///Posts crash summary with application, message, location and fingerprint to Slack.
+///
+///Does nothing if webhook is not configured.
+pub struct SlackNotifier;
+
+impl Notifier for SlackNotifier {
+    fn notify(info: &PanicHookInfo) {
+        if let Some(url) = webhook() {
+            let _ = http::post(&url, &[], "application/json", &message(&Summary::new(info)));
+        }
+    }
+}
+
+//Slack escapes only `&`, `<` and `>` in text.
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn message(summary: &Summary) -> String {
+    let text = format!(":boom: *{} panicked* at `{}`\n```{}```\nFingerprint: `{:016x}`",
+                       escape_mrkdwn(&summary.app), escape_mrkdwn(&summary.location), escape_mrkdwn(&summary.message), summary.fingerprint);
+
+    format!("{{\"text\":\"{}\"}}", escape_json(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::summary::Summary;
+    use super::message;
+
+    #[test]
+    fn should_build_message() {
+        let summary = Summary {
+            app: "app 1.0.0".to_owned(),
+            location: "src/main.rs:1".to_owned(),
+            message: "a < \"b\"".to_owned(),
+            fingerprint: 0xff,
+        };
+
+        assert_eq!(message(&summary), "{\"text\":\":boom: *app 1.0.0 panicked* at `src/main.rs:1`\\n```a &lt; \\\"b\\\"```\\nFingerprint: `00000000000000ff`\"}");
+    }
+}
@@ -0,0 +1,32 @@
+//! Short description of panic for chat-like services.
+
+use PanicHookInfo;
+
+use fingerprint;
+use location;
+use metadata;
+use payload;
+
+pub struct Summary {
+    pub app: String,
+    pub location: String,
+    pub message: String,
+    pub fingerprint: u64,
+}
+
+impl Summary {
+    pub fn new(info: &PanicHookInfo) -> Self {
+        Summary {
+            app: match metadata::app_info() {
+                Some((name, version)) => format!("{} {}", name, version),
+                None => "Application".to_owned(),
+            },
+            location: match info.location() {
+                Some(location) => format!("{}:{}", location::strip(location.file()), location.line()),
+                None => "unknown".to_owned(),
+            },
+            message: payload::to_string(info.payload()),
+            fingerprint: fingerprint::fingerprint(info),
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Windows Error Reporting of panics.
+//!
+//!Report is created with parameters:
+//!
+//!- `P0` - application name from [app_info](../../metadata/fn.app_info.html)
+//!- `P1` - application version
+//!- `P2` - location of panic
+//!- `P3` - panic's message
+//!- `P4` - [fingerprint](../../fingerprint/index.html)
+//!
+//!Report's text with backtrace is attached as file and report is queued, so that
+//!WER collection (e.g. via Group Policy's corporate server) picks it up as usual.
+//!
+//!```rust,no_run
+//!#[macro_use]
+//!extern crate lazy_panic;
+//!
+//!use lazy_panic::formatter::{Notify, Simple};
+//!use lazy_panic::notify::wer::WerNotifier;
+//!
+//!fn main() {
+//!    app_info!();
+//!    set_panic_message!(Notify<Simple, WerNotifier>);
+//!}
+//!```
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::mem;
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::process;
+use std::ptr;
+use std::sync::Mutex;
+use PanicHookInfo;
+
+use windows_sys::Win32::System::ErrorReporting as wer;
+
+use fingerprint;
+use formatter::Debug;
+use location;
+use lock;
+use metadata;
+use payload;
+use super::Notifier;
+
+static EVENT_TYPE: Mutex<Option<String>> = Mutex::new(None);
+
+///Sets event type of reports.
+///
+///Defaults to `RustPanic`.
+pub fn set_event_type<T: Into<String>>(event_type: T) {
+    *lock(&EVENT_TYPE) = Some(event_type.into());
+}
+
+//Zero terminated wide string, truncated to fit `max` characters with terminator.
+fn wide<T: AsRef<OsStr>>(text: T, max: usize) -> Vec<u16> {
+    let mut result: Vec<u16> = text.as_ref().encode_wide().take(max - 1).collect();
+    result.push(0);
+    result
+}
+
+fn copy_wide(dest: &mut [u16], text: &str) {
+    let text = wide(text, dest.len());
+    dest[..text.len()].copy_from_slice(&text);
+}
+
+///Submits panic to Windows Error Reporting.
+///
+///Failure to report is ignored.
+pub struct WerNotifier;
+
+impl Notifier for WerNotifier {
+    fn notify(info: &PanicHookInfo) {
+        let (name, version) = metadata::app_info().unwrap_or(("unknown", "unknown"));
+        let message = payload::to_string(info.payload());
+        let location = match info.location() {
+            Some(location) => format!("{}:{}:{}", location::strip(location.file()), location.line(), location.column()),
+            None => "unknown".to_owned(),
+        };
+
+        let mut text = format!("{} {}\nPanic: {} - {}\n", name, version, location, message);
+        let mut backtrace = Vec::new();
+        let _ = Debug::write_untranslated(&mut backtrace);
+        text.push_str(&String::from_utf8_lossy(&backtrace));
+        let file = env::temp_dir().join(format!("lazy-panic-{}.txt", process::id()));
+        let file = match fs::write(&file, text) {
+            Ok(()) => Some(file),
+            Err(_) => None,
+        };
+
+        let parameters = [name.to_owned(), version.to_owned(), location, message, format!("{:016x}", fingerprint::fingerprint(info))];
+        submit(&parameters, file.as_deref());
+
+        if let Some(file) = file {
+            let _ = fs::remove_file(file);
+        }
+    }
+}
+
+fn submit(parameters: &[String; 5], file: Option<&Path>) {
+    const NAMES: [&str; 5] = ["AppName", "AppVersion", "Location", "Message", "Fingerprint"];
+
+    let event_type = lock(&EVENT_TYPE).clone().unwrap_or_else(|| "RustPanic".to_owned());
+    let event_type = wide(event_type, wer::WER_MAX_EVENT_NAME_LENGTH as usize);
+
+    let mut information = wer::WER_REPORT_INFORMATION {
+        dwSize: mem::size_of::<wer::WER_REPORT_INFORMATION>() as u32,
+        ..Default::default()
+    };
+    copy_wide(&mut information.wzApplicationName, &parameters[0]);
+    copy_wide(&mut information.wzDescription, &parameters[3]);
+
+    unsafe {
+        let mut report = ptr::null_mut();
+        if wer::WerReportCreate(event_type.as_ptr(), wer::WerReportCritical, &information, &mut report) < 0 {
+            return;
+        }
+
+        for (idx, (name, value)) in NAMES.iter().zip(parameters.iter()).enumerate() {
+            let name = wide(name, wer::WER_MAX_PARAM_LENGTH as usize);
+            let value = wide(value, wer::WER_MAX_PARAM_LENGTH as usize);
+            wer::WerReportSetParameter(report, idx as u32, name.as_ptr(), value.as_ptr());
+        }
+
+        if let Some(file) = file {
+            let file = wide(file, usize::MAX);
+            wer::WerReportAddFile(report, file.as_ptr(), wer::WerFileTypeOther, wer::WER_FILE_ANONYMOUS_DATA);
+        }
+
+        let mut result = 0;
+        wer::WerReportSubmit(report, wer::WerConsentNotAsked, wer::WER_SUBMIT_QUEUE | wer::WER_SUBMIT_OUTOFPROCESS, &mut result);
+        wer::WerReportCloseHandle(report);
+    }
+}
@@ -0,0 +1,213 @@
+//! Panicking thread, on behalf of which panic is formatted.
+//!
+//!Hook formats panic on helper thread, so that panic within format can be caught.
+//!State specific to thread is captured from panicking thread beforehand and is used by helper instead of its own.
+
+#[cfg(feature = "backtrace-on")]
+extern crate backtrace;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::fs;
+use std::cell::RefCell;
+use std::thread;
+
+use context;
+
+thread_local!(static ORIGIN: RefCell<Option<(String, Option<u64>)>> = const { RefCell::new(None) });
+#[cfg(feature = "backtrace-on")]
+thread_local!(static BACKTRACE: RefCell<Option<backtrace::Backtrace>> = const { RefCell::new(None) });
+
+///State of panicking thread.
+pub struct Origin {
+    thread_id: String,
+    os_thread_id: Option<u64>,
+    context: Vec<String>,
+    #[cfg(feature = "backtrace-on")]
+    backtrace: backtrace::Backtrace,
+    #[cfg(feature = "tracing")]
+    dispatch: ::tracing::Dispatch,
+    #[cfg(feature = "tracing")]
+    span: ::tracing::Span,
+    #[cfg(feature = "opentelemetry")]
+    otel: ::opentelemetry::Context,
+}
+
+impl Origin {
+    ///Captures state of the current thread.
+    pub fn capture() -> Self {
+        Origin {
+            thread_id: thread_id(),
+            os_thread_id: os_thread_id(),
+            context: context::entries(),
+            //Resolved only if used.
+            #[cfg(feature = "backtrace-on")]
+            backtrace: backtrace::Backtrace::new_unresolved(),
+            #[cfg(feature = "tracing")]
+            dispatch: ::tracing::dispatcher::get_default(Clone::clone),
+            #[cfg(feature = "tracing")]
+            span: ::tracing::Span::current(),
+            #[cfg(feature = "opentelemetry")]
+            otel: ::opentelemetry::Context::current(),
+        }
+    }
+
+    ///Makes the current thread act on behalf of origin, until guard is dropped.
+    pub fn enter(self) -> Guard {
+        let thread = (self.thread_id, self.os_thread_id);
+        ORIGIN.with(move |origin| *origin.borrow_mut() = Some(thread));
+        #[cfg(feature = "backtrace-on")]
+        {
+            let trace = self.backtrace;
+            BACKTRACE.with(move |backtrace| *backtrace.borrow_mut() = Some(trace));
+        }
+
+        #[cfg(feature = "tracing")]
+        let dispatch = ::tracing::dispatcher::set_default(&self.dispatch);
+
+        Guard {
+            #[cfg(feature = "opentelemetry")]
+            _otel: self.otel.attach(),
+            #[cfg(feature = "tracing")]
+            _span: self.span.entered(),
+            #[cfg(feature = "tracing")]
+            _dispatch: dispatch,
+            _context: self.context.into_iter().map(context::push).collect(),
+        }
+    }
+}
+
+///Guard of [Origin::enter](struct.Origin.html#method.enter)
+pub struct Guard {
+    //Fields are dropped in reverse order of entering.
+    #[cfg(feature = "opentelemetry")]
+    _otel: ::opentelemetry::ContextGuard,
+    #[cfg(feature = "tracing")]
+    _span: ::tracing::span::EnteredSpan,
+    #[cfg(feature = "tracing")]
+    _dispatch: ::tracing::subscriber::DefaultGuard,
+    _context: Vec<context::Guard>,
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        let _ = ORIGIN.try_with(|origin| origin.borrow_mut().take());
+        #[cfg(feature = "backtrace-on")]
+        let _ = BACKTRACE.try_with(|backtrace| backtrace.borrow_mut().take());
+    }
+}
+
+///Returns id of the panicking thread, assigned by OS where possible.
+pub fn thread_id() -> String {
+    if let Some(id) = ORIGIN.try_with(|origin| origin.borrow().as_ref().map(|origin| origin.0.clone())).ok().flatten() {
+        return id;
+    }
+
+    match os_thread_id() {
+        Some(id) => id.to_string(),
+        //`ThreadId` provides no stable way to access its number, except `Debug`.
+        None => format!("{:?}", thread::current().id()).trim_start_matches("ThreadId(").trim_end_matches(')').to_owned(),
+    }
+}
+
+///Returns OS id of the panicking thread, if platform provides it.
+pub fn os_thread_id() -> Option<u64> {
+    match ORIGIN.try_with(|origin| origin.borrow().as_ref().map(|origin| origin.1)) {
+        Ok(Some(id)) => id,
+        _ => current_os_thread_id(),
+    }
+}
+
+///Returns backtrace of the panicking thread.
+#[cfg(feature = "backtrace-on")]
+pub fn backtrace() -> backtrace::Backtrace {
+    let origin = BACKTRACE.try_with(|backtrace| backtrace.borrow_mut().as_mut().map(|backtrace| {
+        backtrace.resolve();
+        backtrace.clone()
+    }));
+
+    match origin {
+        Ok(Some(backtrace)) => backtrace,
+        _ => backtrace::Backtrace::new(),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn current_os_thread_id() -> Option<u64> {
+    //Link is `<pid>/task/<tid>`
+    let link = fs::read_link("/proc/thread-self").ok()?;
+    link.file_name()?.to_str()?.parse().ok()
+}
+
+#[cfg(target_os = "macos")]
+fn current_os_thread_id() -> Option<u64> {
+    use std::os::raw::{c_int, c_void};
+    use std::ptr;
+
+    extern "C" {
+        fn pthread_threadid_np(thread: *mut c_void, id: *mut u64) -> c_int;
+    }
+
+    let mut id = 0;
+    //Null stands for the current thread.
+    match unsafe { pthread_threadid_np(ptr::null_mut(), &mut id) } {
+        0 => Some(id),
+        _ => None,
+    }
+}
+
+#[cfg(windows)]
+fn current_os_thread_id() -> Option<u64> {
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThreadId() -> u32;
+    }
+
+    Some(u64::from(unsafe { GetCurrentThreadId() }))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", windows)))]
+fn current_os_thread_id() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use context;
+    use super::{thread_id, Origin};
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn should_get_os_thread_id() {
+        use std::path::Path;
+        use super::os_thread_id;
+
+        let main = os_thread_id().expect("To get tid");
+        let worker = thread::spawn(os_thread_id).join().expect("To join").expect("To get tid");
+        assert_ne!(main, worker);
+        //Thread's task is listed by its OS id.
+        assert!(Path::new(&format!("/proc/self/task/{}", main)).exists());
+    }
+
+    #[test]
+    fn should_act_on_behalf_of_origin() {
+        let _entry = context!("loading {}", "lolka");
+        let origin = Origin::capture();
+        let id = thread_id();
+
+        let helper = thread::spawn(move || {
+            let own = thread_id();
+            let guard = origin.enter();
+            let state = (thread_id(), context::entries());
+            drop(guard);
+
+            (own, state, thread_id(), context::entries())
+        }).join().expect("To join");
+
+        assert_ne!(helper.0, id);
+        assert_eq!(helper.1, (id, vec!["loading lolka".to_owned()]));
+        assert_eq!(helper.2, helper.0);
+        assert!(helper.3.is_empty());
+    }
+}
@@ -0,0 +1,517 @@
+//! Formatting of panic payloads.
+
+use std::any::{Any, TypeId};
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::mem;
+use std::slice;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::str;
+
+use formatter::escape;
+
+type WriteFn = fn(&dyn Any, &mut dyn io::Write) -> Option<io::Result<()>>;
+
+///Describes how to sanitize payload's text before it is written.
+///
+///Implemented for closures `Fn(&str) -> String`
+///
+///Note that redaction is performed inside panic hook, hence it must not panic.
+pub trait Redactor {
+    ///Returns sanitized text.
+    fn redact<'a>(&self, text: &'a str) -> Cow<'a, str>;
+}
+
+impl<F: Fn(&str) -> String> Redactor for F {
+    #[inline]
+    fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        Cow::Owned(self(text))
+    }
+}
+
+static REGISTERED: RwLock<Vec<(TypeId, WriteFn)>> = RwLock::new(Vec::new());
+//`usize::MAX` stands for no limit.
+static MAX_LEN: AtomicUsize = AtomicUsize::new(usize::MAX);
+static REDACTOR: RwLock<Option<Box<dyn Redactor + Send + Sync>>> = RwLock::new(None);
+static ESCAPE: AtomicBool = AtomicBool::new(true);
+//`0` stands for disabled hexdump.
+static HEXDUMP_LEN: AtomicUsize = AtomicUsize::new(0);
+
+//Escapes control characters and invalid UTF-8.
+struct Escaped<'a, W: 'a> {
+    writer: &'a mut W,
+}
+
+impl<'a, W: io::Write> Escaped<'a, W> {
+    #[inline]
+    fn write_str(&mut self, text: &str) -> io::Result<()> {
+        self.writer.write_all(escape::escape_control(text).as_bytes())
+    }
+}
+
+impl<'a, W: io::Write> io::Write for Escaped<'a, W> {
+    fn write(&mut self, mut buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len();
+
+        while !buf.is_empty() {
+            match str::from_utf8(buf) {
+                Ok(text) => {
+                    self.write_str(text)?;
+                    break;
+                },
+                Err(error) => {
+                    let (valid, invalid) = buf.split_at(error.valid_up_to());
+                    //Safe as it is validated by `from_utf8`
+                    self.write_str(unsafe { str::from_utf8_unchecked(valid) })?;
+
+                    let invalid_len = error.error_len().unwrap_or(invalid.len());
+                    for byte in &invalid[..invalid_len] {
+                        write!(self.writer, "\\x{:02x}", byte)?;
+                    }
+                    buf = &invalid[invalid_len..];
+                },
+            }
+        }
+
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+//Writes up to limit, counting the rest as truncated.
+//Limit is applied to raw bytes of payload, before they are redacted or escaped.
+struct Limited<'a, W: 'a> {
+    writer: &'a mut W,
+    remaining: usize,
+    truncated: usize,
+}
+
+impl<'a, W: io::Write> io::Write for Limited<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut len = buf.len().min(self.remaining);
+        if len < buf.len() {
+            //Do not split UTF-8 characters, which have at most 3 continuation bytes.
+            let boundary = len.saturating_sub(3);
+            while len > boundary && buf[len] & 0xC0 == 0x80 {
+                len -= 1;
+            }
+
+            //Once truncated, nothing else is written.
+            self.remaining = 0;
+            self.truncated += buf.len() - len;
+        } else {
+            self.remaining -= len;
+        }
+
+        self.writer.write_all(&buf[..len])?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn write_as<T: Any + fmt::Display>(payload: &dyn Any, writer: &mut dyn io::Write) -> Option<io::Result<()>> {
+    payload.downcast_ref::<T>().map(|payload| write!(writer, "{}", payload))
+}
+
+///Registers type of payload to be formatted using its `Display` implementation.
+///
+///Registered types are handled by [write](fn.write.html), which is used by all built-in formatters.
+///Registering the same type again replaces its previous registration.
+pub fn register<T: Any + fmt::Display>() {
+    register_with(TypeId::of::<T>(), write_as::<T>);
+}
+
+fn register_with(type_id: TypeId, write: WriteFn) {
+    let mut registered = REGISTERED.write().unwrap_or_else(|error| error.into_inner());
+
+    match registered.iter_mut().find(|registered| registered.0 == type_id) {
+        Some(registered) => registered.1 = write,
+        None => registered.push((type_id, write)),
+    }
+}
+
+fn write_error_as<T: Error + 'static>(payload: &dyn Any, writer: &mut dyn io::Write) -> Option<io::Result<()>> {
+    payload.downcast_ref::<T>().map(|payload| {
+        write!(writer, "{}", payload)?;
+
+        let mut source = payload.source();
+        while let Some(error) = source {
+            write!(writer, "\ncaused by: {}", error)?;
+            source = error.source();
+        }
+
+        Ok(())
+    })
+}
+
+///Registers type of error payload to be formatted with its chain of sources.
+///
+///Each source is written on new line as `caused by: {source}`
+pub fn register_error<T: Error + 'static>() {
+    register_with(TypeId::of::<T>(), write_error_as::<T>);
+}
+
+///Sets redactor that sanitizes payload's text before it is written.
+///
+///```rust
+///lazy_panic::payload::set_redactor(|text: &str| text.replace("hunter2", "***"));
+///```
+pub fn set_redactor<R: Redactor + Send + Sync + 'static>(redactor: R) {
+    *REDACTOR.write().unwrap_or_else(|error| error.into_inner()) = Some(Box::new(redactor));
+}
+
+///Removes redactor.
+pub fn reset_redactor() {
+    *REDACTOR.write().unwrap_or_else(|error| error.into_inner()) = None;
+}
+
+///Sets whether to escape control characters and invalid UTF-8 of payload.
+///
+///When enabled, which is default, control characters (except new line and tab) and
+///bidirectional overrides are written as `\x1b` or `\u{202e}`, while invalid UTF-8 bytes are
+///written as `\xff`. This prevents payload from corrupting terminal or spoofing log lines.
+pub fn set_escape(escape: bool) {
+    ESCAPE.store(escape, Ordering::Release);
+}
+
+///Enables hexdump of unknown payloads, limited to `len` bytes.
+///
+///Instead of `Any { .. }`, unknown payload is written as
+///`<unknown payload TypeId(..) of 4 bytes> 2a 00 00 00`
+///
+///Zero `len` disables hexdump, which is default.
+///
+///# Safety
+///
+///Hexdump reads payload's memory as bytes, which is undefined behaviour if payload contains
+///uninitialized bytes (e.g. padding).
+///Caller must ensure that unknown payload types, which can reach panic hook, have no padding.
+pub unsafe fn set_hexdump_fallback(len: usize) {
+    HEXDUMP_LEN.store(len, Ordering::Release);
+}
+
+fn write_hexdump<W: io::Write>(writer: &mut W, payload: &dyn Any, len: usize) -> io::Result<()> {
+    let size = mem::size_of_val(payload);
+    //Safe as long as caller of `set_hexdump_fallback` upholds its contract.
+    let bytes = unsafe {
+        slice::from_raw_parts(payload as *const dyn Any as *const u8, size)
+    };
+
+    write!(writer, "<unknown payload {:?} of {} bytes>", payload.type_id(), size)?;
+    for byte in bytes.iter().take(len) {
+        write!(writer, " {:02x}", byte)?;
+    }
+    match size > len {
+        true => writer.write_all(b" ..."),
+        false => Ok(()),
+    }
+}
+
+///Sets maximum length of payload in bytes.
+///
+///Longer payloads are cut on character boundary, followed by `… (truncated N bytes)`. `None` removes the limit,
+///which is default.
+///
+///Limit applies to payload's text as is, before it is redacted and escaped.
+pub fn set_max_len(len: Option<usize>) {
+    MAX_LEN.store(len.unwrap_or(usize::MAX), Ordering::Release);
+}
+
+///Returns maximum length of payload in bytes, if any.
+pub fn max_len() -> Option<usize> {
+    match MAX_LEN.load(Ordering::Acquire) {
+        usize::MAX => None,
+        len => Some(len),
+    }
+}
+
+///Writes payload.
+///
+///Handles `&str`, `String`, `Cow<str>`, `Box<str>`, bytes (`&[u8]`, `Vec<u8>`) and types
+///registered via [register](fn.register.html). Other types are formatted as `{:?}`
+///
+///With `anyhow` or `eyre` features, `anyhow::Error` and `eyre::Report` are written as full report,
+///including context and causes.
+///
+///Payload's text is truncated according to [max_len](fn.max_len.html), then sanitized by
+///[Redactor](trait.Redactor.html), if any, and escaped according to [set_escape](fn.set_escape.html)
+#[inline]
+pub fn write<W: io::Write>(writer: &mut W, payload: &dyn Any) -> io::Result<()> {
+    write_with(writer, payload, ESCAPE.load(Ordering::Acquire), max_len())
+}
+
+///Writes payload as [write](fn.write.html) does, except escaping of control characters.
+///
+///For formats that escape text on their own (e.g. JSON), so that it is not escaped twice.
+#[inline]
+pub fn write_unescaped<W: io::Write>(writer: &mut W, payload: &dyn Any) -> io::Result<()> {
+    write_with(writer, payload, false, max_len())
+}
+
+fn write_with<W: io::Write>(writer: &mut W, payload: &dyn Any, escape: bool, max_len: Option<usize>) -> io::Result<()> {
+    let mut text = Vec::new();
+    let truncated = {
+        let mut limited = Limited {
+            writer: &mut text,
+            remaining: max_len.unwrap_or(usize::MAX),
+            truncated: 0,
+        };
+        write_full(&mut limited, payload)?;
+        limited.truncated
+    };
+
+    let redactor = REDACTOR.read().unwrap_or_else(|error| error.into_inner());
+    match (redactor.as_ref(), escape) {
+        (Some(redactor), escape) => {
+            let text = String::from_utf8_lossy(&text);
+            let text = redactor.redact(&text);
+            match escape {
+                true => Escaped { writer: &mut *writer }.write_str(&text)?,
+                false => writer.write_all(text.as_bytes())?,
+            }
+        },
+        (None, true) => Escaped { writer: &mut *writer }.write_all(&text)?,
+        (None, false) => writer.write_all(&text)?,
+    }
+
+    match truncated {
+        0 => Ok(()),
+        truncated => write!(writer, "… (truncated {} bytes)", truncated),
+    }
+}
+
+///Formats payload into `String` the same way as [write](fn.write.html) does.
+///
+///Useful for handling payload returned by `catch_unwind`:
+///
+///```rust
+///use std::panic;
+///
+///let result = panic::catch_unwind(|| {
+///    panic!("lolka");
+///});
+///
+///let error = result.unwrap_err();
+///assert_eq!(lazy_panic::payload_to_string(&*error), "lolka");
+///```
+pub fn to_string(payload: &dyn Any) -> String {
+    let mut buffer = Vec::new();
+    //Writing into `Vec` cannot fail.
+    let _ = write(&mut buffer, payload);
+
+    match String::from_utf8(buffer) {
+        Ok(result) => result,
+        Err(error) => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+    }
+}
+
+///Formats payload into `String` as is, without redaction, escaping and truncation.
+///
+///For cases where payload's text is not shown, e.g. computing [fingerprint](../fingerprint/index.html)
+pub fn to_raw_string(payload: &dyn Any) -> String {
+    let mut buffer = Vec::new();
+    //Writing into `Vec` cannot fail.
+    let _ = write_full(&mut buffer, payload);
+
+    match String::from_utf8(buffer) {
+        Ok(result) => result,
+        Err(error) => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+    }
+}
+
+fn write_full<W: io::Write>(writer: &mut W, payload: &dyn Any) -> io::Result<()> {
+    if let Some(payload) = payload.downcast_ref::<&str>() {
+        return writer.write_all(payload.as_bytes());
+    } else if let Some(payload) = payload.downcast_ref::<String>() {
+        return writer.write_all(payload.as_bytes());
+    } else if let Some(payload) = payload.downcast_ref::<Cow<str>>() {
+        return writer.write_all(payload.as_bytes());
+    } else if let Some(payload) = payload.downcast_ref::<Box<str>>() {
+        return writer.write_all(payload.as_bytes());
+    } else if let Some(payload) = payload.downcast_ref::<&[u8]>() {
+        return writer.write_all(payload);
+    } else if let Some(payload) = payload.downcast_ref::<Vec<u8>>() {
+        return writer.write_all(payload);
+    }
+
+    //Debug output of these is full report, including context and causes.
+    #[cfg(feature = "anyhow")]
+    {
+        if let Some(payload) = payload.downcast_ref::<anyhow::Error>() {
+            return write!(writer, "{:?}", payload);
+        }
+    }
+    #[cfg(feature = "eyre")]
+    {
+        if let Some(payload) = payload.downcast_ref::<eyre::Report>() {
+            return write!(writer, "{:?}", payload);
+        }
+    }
+
+    let registered = REGISTERED.read().unwrap_or_else(|error| error.into_inner());
+    for write in registered.iter().map(|registered| registered.1) {
+        if let Some(result) = write(payload, writer) {
+            return result;
+        }
+    }
+
+    match HEXDUMP_LEN.load(Ordering::Acquire) {
+        0 => write!(writer, "{:?}", payload),
+        len => write_hexdump(writer, payload, len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+    use std::fmt;
+    use std::io::Write;
+
+    use super::{register, register_error, set_redactor, reset_redactor, write, write_with, write_hexdump, Limited, Escaped};
+
+    struct Custom;
+
+    impl fmt::Display for Custom {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            fmt.write_str("custom payload")
+        }
+    }
+
+    #[test]
+    fn should_write_registered_payload() {
+        register::<Custom>();
+
+        let mut buffer = Vec::new();
+        write(&mut buffer, &Custom).expect("To write");
+        assert_eq!(buffer, b"custom payload");
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn should_write_anyhow_report() {
+        use anyhow::Context;
+
+        let error = Err::<(), _>(anyhow::anyhow!("inner")).context("outer").unwrap_err();
+        let mut buffer = Vec::new();
+        write(&mut buffer, &error).expect("To write");
+        let buffer = String::from_utf8(buffer).expect("UTF-8");
+        assert!(buffer.starts_with("outer"), "{}", buffer);
+        assert!(buffer.contains("inner"), "{}", buffer);
+    }
+
+    #[cfg(feature = "eyre")]
+    #[test]
+    fn should_write_eyre_report() {
+        use eyre::WrapErr;
+
+        let error = Err::<(), _>(eyre::eyre!("inner")).wrap_err("outer").unwrap_err();
+        let mut buffer = Vec::new();
+        write(&mut buffer, &error).expect("To write");
+        let buffer = String::from_utf8(buffer).expect("UTF-8");
+        assert!(buffer.starts_with("outer"), "{}", buffer);
+        assert!(buffer.contains("inner"), "{}", buffer);
+    }
+
+    #[derive(Debug)]
+    struct Outer(Inner);
+    #[derive(Debug)]
+    struct Inner;
+
+    impl fmt::Display for Outer {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            fmt.write_str("failed to load config")
+        }
+    }
+
+    impl Error for Outer {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    impl fmt::Display for Inner {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            fmt.write_str("file not found")
+        }
+    }
+
+    impl Error for Inner {
+    }
+
+    #[test]
+    fn should_write_error_chain() {
+        register_error::<Outer>();
+
+        let mut buffer = Vec::new();
+        write(&mut buffer, &Outer(Inner)).expect("To write");
+        assert_eq!(buffer, &b"failed to load config\ncaused by: file not found"[..]);
+    }
+
+    #[test]
+    fn should_limit_output() {
+        let mut buffer = Vec::new();
+        let truncated = {
+            let mut writer = Limited {
+                writer: &mut buffer,
+                remaining: 5,
+                truncated: 0,
+            };
+            writer.write_all("lolk😀 lolka".as_bytes()).expect("To write");
+            writer.write_all(b"lolka").expect("To write");
+            writer.truncated
+        };
+
+        assert_eq!(buffer, b"lolk");
+        assert_eq!(truncated, 15);
+    }
+
+    #[test]
+    fn should_limit_payload_before_escaping() {
+        let mut buffer = Vec::new();
+        write_with(&mut buffer, &"\x1b\x1b\x1b\x1b\x1b\x1b", true, Some(4)).expect("To write");
+        assert_eq!(String::from_utf8(buffer).expect("UTF-8"), "\\x1b\\x1b\\x1b\\x1b… (truncated 2 bytes)");
+
+        let mut buffer = Vec::new();
+        write_with(&mut buffer, &"\u{202e}\u{202e}", true, Some(4)).expect("To write");
+        assert_eq!(String::from_utf8(buffer).expect("UTF-8"), "\\u{202e}… (truncated 3 bytes)");
+    }
+
+    #[test]
+    fn should_redact_payload() {
+        set_redactor(|text: &str| text.replace("hunter2", "***"));
+
+        let mut buffer = Vec::new();
+        let result = write(&mut buffer, &"password is hunter2");
+        reset_redactor();
+
+        result.expect("To write");
+        assert_eq!(buffer, b"password is ***");
+    }
+
+    #[test]
+    fn should_escape_output() {
+        let mut buffer = Vec::new();
+        Escaped { writer: &mut buffer }.write_all(b"\x1b[31mred\x1b[0m\r\n\tok \xff\xfe\xe2\x80\xae \xc2\x85").expect("To write");
+        assert_eq!(String::from_utf8(buffer).expect("UTF-8"), "\\x1b[31mred\\x1b[0m\\x0d\n\tok \\xff\\xfe\\u{202e} \\u{85}");
+    }
+
+    #[test]
+    fn should_write_hexdump() {
+        let mut buffer = Vec::new();
+        write_hexdump(&mut buffer, &0x0102_0304u32, 2).expect("To write");
+        let output = String::from_utf8(buffer).expect("UTF-8");
+
+        assert!(output.starts_with("<unknown payload TypeId("));
+        assert!(output.ends_with(" of 4 bytes> 04 03 ...") || output.ends_with(" of 4 bytes> 01 02 ..."));
+    }
+}
@@ -0,0 +1,81 @@
+//! Mapping of known panics to documentation.
+//!
+//!Registered URLs are written by [Runbook](../formatter/struct.Runbook.html) as `See: <url>`,
+//!turning recurring operational panics into self-service runbook pointers.
+
+use std::sync::Mutex;
+use PanicHookInfo;
+
+use fingerprint;
+use location;
+use lock;
+
+enum Key {
+    Fingerprint(u64),
+    Location(String, u32),
+}
+
+static RUNBOOKS: Mutex<Vec<(Key, String)>> = Mutex::new(Vec::new());
+
+///Registers URL for panics with specified [fingerprint](../fingerprint/index.html)
+pub fn register_fingerprint<T: Into<String>>(fingerprint: u64, url: T) {
+    lock(&RUNBOOKS).push((Key::Fingerprint(fingerprint), url.into()));
+}
+
+///Registers URL for panics at specified location.
+///
+///File is compared after stripping according to [location](../location/index.html) configuration.
+pub fn register_location<F: Into<String>, T: Into<String>>(file: F, line: u32, url: T) {
+    lock(&RUNBOOKS).push((Key::Location(file.into(), line), url.into()));
+}
+
+///Removes all registered URLs.
+pub fn clear() {
+    lock(&RUNBOOKS).clear();
+}
+
+///Returns URL registered for panic, if any.
+///
+///When multiple URLs match, the first registered is returned.
+pub fn lookup(info: &PanicHookInfo) -> Option<String> {
+    let runbooks = lock(&RUNBOOKS);
+    if runbooks.is_empty() {
+        return None;
+    }
+
+    let fingerprint = fingerprint::fingerprint(info);
+    runbooks.iter().find(|runbook| match runbook.0 {
+        Key::Fingerprint(expected) => expected == fingerprint,
+        Key::Location(ref file, line) => match info.location() {
+            Some(location) => location.line() == line && location::strip(location.file()) == location::strip(file),
+            None => false,
+        },
+    }).map(|runbook| runbook.1.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use formatter::{Composite, Empty, Fingerprint, Runbook};
+    use test::{capture_report, format_panic};
+    use super::{register_fingerprint, register_location};
+
+    type FingerprintFormat = Composite<Empty, Fingerprint, Empty, Empty, io::Sink>;
+    type RunbookFormat = Composite<Empty, Runbook, Empty, Empty, io::Sink>;
+
+    #[test]
+    fn should_write_runbook() {
+        let fingerprint = format_panic::<FingerprintFormat, _>("runbook lolka");
+        let fingerprint = u64::from_str_radix(fingerprint.trim_start_matches("fingerprint="), 16).expect("To parse fingerprint");
+        register_fingerprint(fingerprint, "https://wiki.example.com/lolka");
+
+        assert_eq!(format_panic::<RunbookFormat, _>("runbook lolka"), "\nSee: https://wiki.example.com/lolka");
+        assert_eq!(format_panic::<RunbookFormat, _>("runbook kek"), "");
+
+        let line = line!() + 2;
+        register_location("src/runbook.rs", line, "https://wiki.example.com/location");
+        let report = capture_report::<RunbookFormat, _, _>(|| panic!("runbook location"));
+        assert_eq!(report.as_deref(), Some("\nSee: https://wiki.example.com/location"));
+    }
+}